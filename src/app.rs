@@ -0,0 +1,2237 @@
+use crate::config::{
+    Bend, BlendMode, ClusterColoring, HowMany, LayerColor, LchChannel, LinkBias, MazeAlgorithm,
+    Mode, ModelProperties, NoiseKind, Size, StringDirection, StringEnd, StringLayer, StringShape,
+    Tiling, Variant,
+};
+use crate::download::download;
+use crate::machine::MachineSettings;
+use crate::palettes::{
+    self,
+    generate::{self, Harmony},
+    vision::{self, Deficiency},
+    Palette,
+};
+use crate::permalink;
+use crate::scene::{path_data, Scene, Shape, Style};
+use crate::tiling;
+use gloo::events::EventListener;
+use gloo::file::callbacks::FileReader;
+use gloo::storage::{LocalStorage, Storage};
+use rand::Rng;
+use yew::prelude::*;
+
+const STORAGE_KEY: &str = "yew.genny.database";
+
+pub struct Model {
+    p: ModelProperties,
+    export: ExportSettings,
+    _hash_listener: EventListener,
+    /// Reads a palette file picked for import.
+    palette_reader: Option<FileReader>,
+    /// Why the last palette import failed.
+    palette_error: Option<String>,
+    /// Color-vision deficiency simulated in the preview, not in exports.
+    vision: Option<Deficiency>,
+}
+
+/// How exports are rendered. Unlike [`ModelProperties`], these don't affect
+/// the drawing and aren't persisted.
+struct ExportSettings {
+    png_width: u32,
+    png_height: u32,
+    png_transparent: bool,
+    machine: MachineSettings,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            png_width: 4000,
+            png_height: 4000,
+            png_transparent: false,
+            machine: MachineSettings::default(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub enum Msg {
+    ToggleArrows,
+    TogglePaths,
+    ToggleCircles,
+    UpdateColor(String),
+    UpdateVariant(String),
+    UpdateSize(Size),
+    UpdateMode(Mode),
+    UpdateStringsRadius(f32),
+    UpdateStringsSplits(usize),
+    ToggleStringsBase,
+    Layers(LayersMsg),
+    Curve(CurveMsg),
+    StringEnd(End, StringEndMsg),
+    Cells(CellsMsg),
+    Contour(ContourMsg),
+    Links(LinksMsg),
+    UpdateSquaresTiling(Tiling),
+    UpdateSquaresColoring(ClusterColoring),
+    Drift(DriftMsg),
+    Maze(MazeMsg),
+    UpdateSeed(String),
+    ToggleSeedLock,
+    RerollSeed,
+    LoadPermalink(ModelProperties),
+    Palette(PaletteMsg),
+    UpdateVision(Option<Deficiency>),
+    Export(ExportMsg),
+}
+
+/// Edits to the selected custom palette.
+pub enum PaletteMsg {
+    /// Adds a copy of the selected palette and selects it.
+    New,
+    Rename(String),
+    Delete,
+    MoveUp,
+    MoveDown,
+    UpdateColor(usize, String),
+    AddColor,
+    RemoveColor(usize),
+    /// Reads a palette file and adds it as a new palette.
+    Import(gloo::file::File),
+    Imported(String, Result<Vec<u8>, String>),
+    Generator(GeneratorMsg),
+}
+
+/// Changes to the settings of the generated palette.
+pub enum GeneratorMsg {
+    UpdateHarmony(Harmony),
+    UpdateCount(usize),
+    UpdateLightness(f32, f32),
+    UpdateChroma(f32, f32),
+    /// Generates different colors with the same settings.
+    Regenerate,
+}
+
+/// One of the two shapes of Strings mode.
+#[derive(Clone, Copy)]
+pub enum End {
+    Inner,
+    Outer,
+}
+
+/// Changes to one of the shapes of Strings mode.
+pub enum StringEndMsg {
+    Shape(StringShape),
+    /// Center, as fractions of the canvas width and height.
+    Center(f32, f32),
+    Rotation(f32),
+    Scale(f32),
+}
+
+/// Changes to the layers of Strings mode.
+pub enum LayersMsg {
+    /// Adds a copy of the last layer.
+    Add,
+    Remove(usize),
+    Update(usize, LayerMsg),
+}
+
+pub enum LayerMsg {
+    Aperture(usize),
+    Direction(StringDirection),
+    Color(LayerColor),
+    StrokeWidth(f32),
+    Opacity(f32),
+    Blend(BlendMode),
+}
+
+/// Changes to how strings bend.
+pub enum CurveMsg {
+    Bend(Bend),
+    Cubic(bool),
+    Strength(f32),
+    /// As fractions of the canvas width and height.
+    Attractor(f32, f32),
+    Frequency(f32),
+}
+
+/// Changes to the size and look of grid cells.
+pub enum CellsMsg {
+    Size(usize),
+    Gap(usize),
+    CornerRadius(f32),
+    StrokeWidth(f32),
+}
+
+/// Changes to the paths of the Contour variant.
+pub enum ContourMsg {
+    Radius(f32),
+    Offset(f32),
+}
+
+/// Changes to how likely squares are to link up.
+pub enum LinksMsg {
+    Right(f32),
+    Down(f32),
+    Bias(LinkBias),
+    MaxClusterSize(Option<usize>),
+}
+
+/// Changes to how Squares mode shifts colors across the canvas.
+pub enum DriftMsg {
+    Amount(f32),
+    Frequency(f32),
+    Octaves(usize),
+    Noise(NoiseKind),
+    Channel(LchChannel),
+}
+
+/// Changes to the maze and its solution.
+pub enum MazeMsg {
+    Algorithm(MazeAlgorithm),
+    ToggleSolve,
+    Start(usize, usize),
+    End(usize, usize),
+}
+
+pub enum ExportMsg {
+    Svg,
+    PlotterSvg,
+    Png,
+    UpdatePngWidth(u32),
+    UpdatePngHeight(u32),
+    TogglePngTransparent,
+    Gcode,
+    Hpgl,
+    UpdateWidthMm(u32),
+    UpdateHeightMm(u32),
+    UpdateFeedRate(u32),
+}
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+    fn create(ctx: &yew::Context<Self>) -> Self {
+        let saved = match LocalStorage::get::<serde_json::Value>(STORAGE_KEY) {
+            Ok(saved) => ModelProperties::from_value(saved).unwrap_or_else(|e| {
+                log!("discarding saved settings: {}", e);
+                Default::default()
+            }),
+            Err(_) => Default::default(),
+        };
+        // a permalink wins over whatever was last drawn in this browser, and
+        // must show exactly the drawing it was made from
+        let from_link = permalink::read();
+        let linked = from_link.is_some();
+        let mut p = match from_link {
+            Some(p) => keep_palettes(p, &saved.custom_palettes),
+            None => saved,
+        };
+        if p.palette(&p.color_scheme).is_none() {
+            log!("color scheme invalid: {}", p.color_scheme);
+            p.color_scheme = ModelProperties::default().color_scheme;
+        }
+        if !p.seed_locked && !linked {
+            p.seed = rand::thread_rng().gen();
+        }
+        permalink::write(&p);
+        let link = ctx.link().clone();
+        Self {
+            p,
+            export: Default::default(),
+            _hash_listener: permalink::on_change(move |p| link.send_message(Msg::LoadPermalink(p))),
+            palette_reader: None,
+            palette_error: None,
+            vision: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        // any settings change draws a new image, unless the seed is locked
+        let reseed = !matches!(
+            msg,
+            Msg::UpdateSeed(_)
+                | Msg::ToggleSeedLock
+                | Msg::RerollSeed
+                | Msg::LoadPermalink(_)
+                | Msg::Palette(_)
+                | Msg::UpdateVision(_)
+        );
+        match msg {
+            Msg::UpdateMode(mode) => {
+                match mode {
+                    Mode::Squares(_) => {
+                        self.p.circles_enabled = false;
+                        self.p.paths_enabled = false;
+                        self.p.arrows_enabled = false;
+                    }
+                    Mode::Strings(_) => {
+                        self.p.circles_enabled = false;
+                        self.p.paths_enabled = false;
+                        self.p.arrows_enabled = false;
+                    }
+                    Mode::Maze(_) => {
+                        self.p.circles_enabled = false;
+                        self.p.paths_enabled = false;
+                        self.p.arrows_enabled = false;
+                    }
+                };
+                self.p.mode = mode;
+            }
+            Msg::ToggleArrows => self.p.arrows_enabled = !self.p.arrows_enabled,
+            Msg::TogglePaths => self.p.paths_enabled = !self.p.paths_enabled,
+            Msg::ToggleCircles => self.p.circles_enabled = !self.p.circles_enabled,
+            Msg::UpdateColor(color_scheme) => {
+                if self.p.palette(&color_scheme).is_some() {
+                    self.p.color_scheme = color_scheme;
+                } else {
+                    log!("color scheme invalid: {}", color_scheme);
+                }
+            }
+            Msg::UpdateVariant(variant) => {
+                let variant = if variant == "Outline" {
+                    Variant::Outline
+                } else if variant == "Filled" {
+                    Variant::Filled
+                } else if variant == "Contour" {
+                    Variant::Contour
+                } else {
+                    unreachable!()
+                };
+                self.p.variant = variant;
+            }
+            Msg::UpdateSize(size) => {
+                self.p.size = size;
+                self.p.fit_cells();
+            }
+            Msg::UpdateStringsRadius(scale) => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => props.inner.scale = scale,
+            },
+            Msg::ToggleStringsBase => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => props.show_base = !props.show_base,
+            },
+            Msg::StringEnd(end, msg) => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => {
+                    let end = match end {
+                        End::Inner => &mut props.inner,
+                        End::Outer => &mut props.outer,
+                    };
+                    match msg {
+                        StringEndMsg::Shape(shape) => end.shape = shape,
+                        StringEndMsg::Center(x, y) => end.center = (x, y),
+                        StringEndMsg::Rotation(rotation) => end.rotation = rotation,
+                        StringEndMsg::Scale(scale) => end.scale = scale,
+                    }
+                }
+            },
+            Msg::UpdateStringsSplits(splits) => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => {
+                    for layer in props.layers.iter_mut() {
+                        let current_aperture_as_percent =
+                            layer.aperture as f32 / props.splits.max(1) as f32;
+                        let next_aperture =
+                            (current_aperture_as_percent * splits as f32).round() as usize;
+                        layer.aperture = next_aperture;
+                    }
+                    props.splits = splits;
+                }
+            },
+            Msg::Curve(msg) => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => {
+                    let curve = &mut props.curve;
+                    match msg {
+                        CurveMsg::Bend(bend) => curve.bend = bend,
+                        CurveMsg::Cubic(cubic) => curve.cubic = cubic,
+                        CurveMsg::Strength(strength) => curve.strength = strength,
+                        CurveMsg::Attractor(x, y) => curve.attractor = (x, y),
+                        CurveMsg::Frequency(frequency) => curve.frequency = frequency,
+                    }
+                }
+            },
+            Msg::Layers(msg) => match self.p.mode {
+                Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+                Mode::Strings(ref mut props) => match msg {
+                    LayersMsg::Add => {
+                        let layer = props.layers.last().copied().unwrap_or_default();
+                        props.layers.push(layer);
+                    }
+                    LayersMsg::Remove(i) => {
+                        props.layers.remove(i);
+                    }
+                    LayersMsg::Update(i, msg) => {
+                        let layer = &mut props.layers[i];
+                        match msg {
+                            LayerMsg::Aperture(aperture) => layer.aperture = aperture,
+                            LayerMsg::Direction(direction) => layer.direction = direction,
+                            LayerMsg::Color(color) => layer.color = color,
+                            LayerMsg::StrokeWidth(width) => layer.stroke_width = width,
+                            LayerMsg::Opacity(opacity) => layer.opacity = opacity,
+                            LayerMsg::Blend(blend) => layer.blend = blend,
+                        }
+                    }
+                },
+            },
+            Msg::Cells(msg) => {
+                let cells = &mut self.p.cells;
+                match msg {
+                    CellsMsg::Size(size) => cells.size = size,
+                    CellsMsg::Gap(gap) => cells.gap = gap,
+                    CellsMsg::CornerRadius(radius) => cells.corner_radius = radius,
+                    CellsMsg::StrokeWidth(width) => cells.stroke_width = width,
+                }
+                self.p.fit_cells();
+            }
+            Msg::Contour(msg) => match self.p.mode {
+                Mode::Squares(ref mut props) => match msg {
+                    ContourMsg::Radius(radius) => props.contour.radius = radius,
+                    ContourMsg::Offset(offset) => props.contour.offset = offset,
+                },
+                Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+            },
+            Msg::Links(msg) => match self.p.mode {
+                Mode::Squares(ref mut props) => {
+                    let links = &mut props.links;
+                    match msg {
+                        LinksMsg::Right(chance) => links.right = chance,
+                        LinksMsg::Down(chance) => links.down = chance,
+                        LinksMsg::Bias(bias) => links.bias = bias,
+                        LinksMsg::MaxClusterSize(max) => links.max_cluster_size = max,
+                    }
+                }
+                Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+            },
+            Msg::UpdateSquaresTiling(tiling) => match self.p.mode {
+                Mode::Squares(ref mut props) => props.tiling = tiling,
+                Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+            },
+            Msg::UpdateSquaresColoring(coloring) => match self.p.mode {
+                Mode::Squares(ref mut props) => props.coloring = coloring,
+                Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+            },
+            Msg::Maze(msg) => match self.p.mode {
+                Mode::Maze(ref mut props) => match msg {
+                    MazeMsg::Algorithm(algorithm) => props.algorithm = algorithm,
+                    MazeMsg::ToggleSolve => props.solve = !props.solve,
+                    MazeMsg::Start(column, row) => props.start = (column, row),
+                    MazeMsg::End(column, row) => props.end = Some((column, row)),
+                },
+                Mode::Squares(_) | Mode::Strings(_) => unreachable!(),
+            },
+            Msg::Drift(msg) => match self.p.mode {
+                Mode::Squares(ref mut props) => {
+                    let drift = &mut props.drift;
+                    match msg {
+                        DriftMsg::Amount(amount) => drift.amount = amount,
+                        DriftMsg::Frequency(frequency) => drift.frequency = frequency,
+                        DriftMsg::Octaves(octaves) => drift.octaves = octaves,
+                        DriftMsg::Noise(noise) => drift.noise = noise,
+                        DriftMsg::Channel(channel) => drift.channel = channel,
+                    }
+                }
+                Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+            },
+            Msg::UpdateSeed(seed) => match seed.trim().parse() {
+                Ok(seed) => {
+                    // a seed typed in by hand is one the user wants to keep
+                    self.p.seed = seed;
+                    self.p.seed_locked = true;
+                }
+                Err(_) => log!("seed invalid: {}", seed),
+            },
+            Msg::ToggleSeedLock => self.p.seed_locked = !self.p.seed_locked,
+            Msg::RerollSeed => self.p.seed = rand::thread_rng().gen(),
+            Msg::LoadPermalink(p) => {
+                let p = keep_palettes(p, &self.p.custom_palettes);
+                if p.palette(&p.color_scheme).is_some() {
+                    self.p = p;
+                } else {
+                    log!("color scheme invalid: {}", p.color_scheme);
+                }
+            }
+            Msg::Palette(msg) => self.update_palette(ctx, msg),
+            Msg::UpdateVision(vision) => {
+                self.vision = vision;
+                return true;
+            }
+            Msg::Export(msg) => return self.update_export(msg),
+        }
+        if reseed && !self.p.seed_locked {
+            self.p.seed = rand::thread_rng().gen();
+        }
+        LocalStorage::set(STORAGE_KEY, &self.p).expect("failed to set");
+        permalink::write(&self.p);
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let mut scene = crate::generate(&self.p);
+        if let Some(deficiency) = self.vision {
+            scene = scene.map_colors(&|c| vision::simulate(c, deficiency));
+        }
+        html! {
+            <div class="container">
+                <div class="row align-items-center">
+                    <div class="col-sm-9">
+                        { render_scene(&scene) }
+                    </div>
+                    {
+                        match self.p.mode {
+                            Mode::Squares(_) => {
+                                self.render_squares_options(ctx)
+                            },
+                            Mode::Strings(_) => {
+                                self.render_strings_options(ctx)
+                            }
+                            Mode::Maze(_) => {
+                                self.render_maze_options(ctx)
+                            }
+                        }
+                    }
+                </div>
+                <div class="row text-center">
+                    <div class="col-sm-9">
+                    {"Choose genny mode: " }
+                    <br/>
+                    {
+                        self.render_mode_options(ctx)
+                    }
+                    </div>
+                </div>
+                <div class="row text-center">
+                    <div class="col-sm-9">
+                    {"Seed: " }
+                    <br/>
+                    {
+                        self.render_seed_options(ctx)
+                    }
+                    </div>
+                </div>
+                <div class="row text-center" style="margin-top:1em">
+                    <div class="col-sm-9">
+                    {
+                        self.render_export_options(ctx)
+                    }
+                    </div>
+                </div>
+                <div class="row text-center" style="margin-top:0.5em">
+                    <div class="col-sm-9">
+                    {
+                        self.render_png_options(ctx)
+                    }
+                    </div>
+                </div>
+                <div class="row text-center" style="margin-top:0.5em">
+                    <div class="col-sm-9">
+                    {
+                        self.render_machine_options(ctx)
+                    }
+                    </div>
+                </div>
+                /*
+                <input
+                    type="checkbox"
+                    id="toggle_arrows"
+                    checked=self.p.arrows_enabled
+                    onclick=self.link.callback(|_| Msg::ToggleArrows)
+                />
+                {" render arrows" }
+                <br/>
+                <input
+                    type="checkbox"
+                    id="toggle_circles"
+                    checked=self.p.circles_enabled
+                    onclick=self.link.callback(|_| Msg::ToggleCircles)
+                />
+                {" render circles" }
+                <br/>
+                <input
+                    type="checkbox"
+                    id="toggle_squares"
+                    checked=self.p.squares_enabled
+                    onclick=self.link.callback(|_| Msg::ToggleSquares)
+                />
+                {" render squares" }
+                <br/>
+                <input
+                    type="checkbox"
+                    id="toggle_paths"
+                    checked=self.p.paths_enabled
+                    onclick=self.link.callback(|_| Msg::TogglePaths)
+                />
+                {" render paths" }
+                <br/>*/
+            </div>
+        }
+    }
+}
+
+impl Model {
+    fn render_color_options(&self, ctx: &Context<Self>) -> Html {
+        let option = |palette: &Palette| {
+            html! {
+                <option value={palette.name.clone()} selected={self.p.color_scheme == palette.name}>
+                    {&palette.name}
+                </option>
+            }
+        };
+        html! {
+            <>
+            <select name="colors" id="colors" onchange={ctx.link().callback(|e: Event|{
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateColor(select.value())
+            })}>
+                { palettes::builtin().iter().map(option).collect::<Html>() }
+                { option(&self.p.palette_generator.generate()) }
+                if !self.p.custom_palettes.is_empty() {
+                    <optgroup label="custom">
+                        { self.p.custom_palettes.iter().map(option).collect::<Html>() }
+                    </optgroup>
+                }
+            </select>
+            if self.p.color_scheme == generate::NAME {
+                { self.render_generator_options(ctx) }
+            }
+            { self.render_palette_editor(ctx) }
+            </>
+        }
+    }
+
+    /// Color pickers for the selected palette, editable for custom palettes
+    /// only. Built-in palettes can be copied into a custom one.
+    fn render_palette_editor(&self, ctx: &Context<Self>) -> Html {
+        let editable = !palettes::is_reserved(&self.p.color_scheme);
+        let swatches = self.p.colors().into_iter().enumerate().map(|(i, color)| {
+            html! {
+                <span style="white-space:nowrap">
+                    <input
+                        type="color"
+                        value={color.to_lowercase()}
+                        disabled={!editable}
+                        onchange={ctx.link().callback(move |e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            Msg::Palette(PaletteMsg::UpdateColor(i, input.value()))
+                        })}/>
+                    if editable && self.p.colors().len() > 1 {
+                        <button
+                            type="button"
+                            class="btn btn-link btn-sm p-0"
+                            title="remove color"
+                            onclick={ctx.link().callback(move |_| Msg::Palette(PaletteMsg::RemoveColor(i)))}>
+                            {"×"}
+                        </button>
+                    }
+                </span>
+            }
+        });
+        let button = |label: &str, title: &str, msg: fn() -> PaletteMsg| {
+            html! {
+                <button
+                    type="button"
+                    class="btn btn-secondary btn-sm"
+                    style="margin:0.1em"
+                    title={title.to_owned()}
+                    onclick={ctx.link().callback(move |_| Msg::Palette(msg()))}>
+                    {label}
+                </button>
+            }
+        };
+        html! {
+            <div style="margin-top:0.3em">
+                <div>
+                    { swatches.collect::<Html>() }
+                    if editable {
+                        { button("+", "add color", || PaletteMsg::AddColor) }
+                    }
+                </div>
+                if editable {
+                    <input
+                        type="text"
+                        class="form-control form-control-sm"
+                        style="margin-top:0.3em"
+                        value={self.p.color_scheme.clone()}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            Msg::Palette(PaletteMsg::Rename(input.value()))
+                        })}/>
+                }
+                <div>
+                    { button("new palette", "copy this palette", || PaletteMsg::New) }
+                    if editable {
+                        { button("↑", "move up", || PaletteMsg::MoveUp) }
+                        { button("↓", "move down", || PaletteMsg::MoveDown) }
+                        { button("delete", "delete this palette", || PaletteMsg::Delete) }
+                    }
+                </div>
+                <div>
+                    <label for="import_palette" class="btn btn-secondary btn-sm" style="margin:0.1em">
+                        {"import palette"}
+                    </label>
+                    <input
+                        type="file"
+                        id="import_palette"
+                        accept=".gpl,.ase,.txt,.hex,.css"
+                        style="display:none"
+                        onchange={ctx.link().batch_callback(|e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            let file = input.files().and_then(|files| files.get(0));
+                            input.set_value("");
+                            file.map(|file| Msg::Palette(PaletteMsg::Import(file.into())))
+                        })}/>
+                </div>
+                if let Some(error) = &self.palette_error {
+                    <div class="text-danger small">{error}</div>
+                }
+                { self.render_vision_options(ctx) }
+            </div>
+        }
+    }
+
+    /// Simulates color-vision deficiencies in the preview, and warns about
+    /// neighbouring colors of the palette that are hard to tell apart.
+    fn render_vision_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div style="margin-top:0.3em">
+                {"Preview as: "}
+                <select name="vision" id="vision" onchange={ctx.link().callback(|e: Event| {
+                    let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                    Msg::UpdateVision(select.value().parse().ok())
+                })}>
+                    <option value="" selected={self.vision.is_none()}>{"typical vision"}</option>
+                    {
+                        Deficiency::ALL.iter().map(|d| html! {
+                            <option value={d.to_string()} selected={self.vision == Some(*d)}>
+                                {d.to_string().to_lowercase()}
+                            </option>
+                        }).collect::<Html>()
+                    }
+                </select>
+                {
+                    vision::analyze(&self.p.colors()).iter().map(|warning| html! {
+                        <div class="text-warning small">{warning.to_string()}</div>
+                    }).collect::<Html>()
+                }
+            </div>
+        }
+    }
+
+    /// Settings of the generated palette: harmony, number of colors, and
+    /// lightness and chroma ranges.
+    fn render_generator_options(&self, ctx: &Context<Self>) -> Html {
+        let generator = &self.p.palette_generator;
+        let number = |id: &str, value: f32, max: f32, msg: Box<dyn Fn(f32) -> GeneratorMsg>| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    min="0"
+                    max={max.to_string()}
+                    id={id.to_owned()}
+                    value={value.round().to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::Palette(PaletteMsg::Generator(msg(v.clamp(0.0, max)))))
+                    })}/>
+            }
+        };
+        let (l_min, l_max) = generator.lightness;
+        let (c_min, c_max) = generator.chroma;
+        html! {
+            <div style="margin-top:0.3em">
+                <div class="input-group input-group-sm justify-content-center">
+                    <select name="harmony" id="harmony" onchange={ctx.link().callback(|e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        Msg::Palette(PaletteMsg::Generator(GeneratorMsg::UpdateHarmony(select.value().parse().unwrap())))
+                    })}>
+                    {
+                        Harmony::ALL.iter().map(|harmony| html! {
+                            <option value={harmony.to_string()} selected={generator.harmony == *harmony}>
+                                {harmony.to_string()}
+                            </option>
+                        }).collect::<Html>()
+                    }
+                    </select>
+                    <input
+                        type="number"
+                        class="form-control"
+                        style="max-width:4em"
+                        min="2"
+                        max="12"
+                        id="palette_count"
+                        title="colors"
+                        value={generator.count.to_string()}
+                        onchange={ctx.link().batch_callback(|e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            input.value().parse::<usize>().ok()
+                                .map(|v| Msg::Palette(PaletteMsg::Generator(GeneratorMsg::UpdateCount(v.clamp(2, 12)))))
+                        })}/>
+                    <div class="input-group-append">
+                        <button
+                            type="button"
+                            class="btn btn-secondary"
+                            onclick={ctx.link().callback(|_| Msg::Palette(PaletteMsg::Generator(GeneratorMsg::Regenerate)))}>
+                            {"regenerate"}
+                        </button>
+                    </div>
+                </div>
+                <div class="input-group input-group-sm justify-content-center">
+                    <div class="input-group-prepend">
+                        <span class="input-group-text">{"lightness"}</span>
+                    </div>
+                    { number("lightness_min", l_min, 100.0, Box::new(move |v| GeneratorMsg::UpdateLightness(v, l_max))) }
+                    { number("lightness_max", l_max, 100.0, Box::new(move |v| GeneratorMsg::UpdateLightness(l_min, v))) }
+                </div>
+                <div class="input-group input-group-sm justify-content-center">
+                    <div class="input-group-prepend">
+                        <span class="input-group-text">{"chroma"}</span>
+                    </div>
+                    { number("chroma_min", c_min, 130.0, Box::new(move |v| GeneratorMsg::UpdateChroma(v, c_max))) }
+                    { number("chroma_max", c_max, 130.0, Box::new(move |v| GeneratorMsg::UpdateChroma(c_min, v))) }
+                </div>
+            </div>
+        }
+    }
+
+    /// Adds `palette` after the other custom palettes, renamed if its name
+    /// is taken, and selects it.
+    fn add_palette(&mut self, mut palette: Palette) {
+        palette.name = palettes::unique_name(&palette.name, &self.p.palettes());
+        self.p.color_scheme = palette.name.clone();
+        self.p.custom_palettes.push(palette);
+    }
+
+    fn update_palette(&mut self, ctx: &Context<Self>, msg: PaletteMsg) {
+        let msg = match msg {
+            PaletteMsg::New => {
+                self.add_palette(Palette {
+                    name: "custom".to_owned(),
+                    colors: self.p.colors(),
+                });
+                return;
+            }
+            PaletteMsg::Import(file) => {
+                let link = ctx.link().clone();
+                let name = file.name();
+                self.palette_reader = Some(gloo::file::callbacks::read_as_bytes(
+                    &file,
+                    move |contents| {
+                        let contents = contents.map_err(|e| e.to_string());
+                        link.send_message(Msg::Palette(PaletteMsg::Imported(name, contents)))
+                    },
+                ));
+                return;
+            }
+            PaletteMsg::Imported(name, contents) => {
+                self.palette_reader.take();
+                match contents.and_then(|c| palettes::import::parse(&name, &c)) {
+                    Ok(palette) => {
+                        self.palette_error = None;
+                        self.add_palette(palette);
+                    }
+                    Err(e) => self.palette_error = Some(e),
+                }
+                return;
+            }
+            PaletteMsg::Generator(msg) => {
+                let generator = &mut self.p.palette_generator;
+                match msg {
+                    GeneratorMsg::UpdateHarmony(harmony) => generator.harmony = harmony,
+                    GeneratorMsg::UpdateCount(count) => generator.count = count,
+                    GeneratorMsg::UpdateLightness(min, max) => generator.lightness = (min, max),
+                    GeneratorMsg::UpdateChroma(min, max) => generator.chroma = (min, max),
+                    GeneratorMsg::Regenerate => generator.seed = rand::thread_rng().gen(),
+                }
+                return;
+            }
+            msg => msg,
+        };
+        let custom = &mut self.p.custom_palettes;
+        let index = match custom.iter().position(|p| p.name == self.p.color_scheme) {
+            Some(index) => index,
+            None => {
+                log!("built-in palettes can't be edited");
+                return;
+            }
+        };
+        match msg {
+            PaletteMsg::New
+            | PaletteMsg::Import(_)
+            | PaletteMsg::Imported(..)
+            | PaletteMsg::Generator(_) => unreachable!(),
+            PaletteMsg::Rename(name) => {
+                let name = name.trim().to_owned();
+                let taken = palettes::is_reserved(&name)
+                    || custom
+                        .iter()
+                        .enumerate()
+                        .any(|(i, p)| i != index && p.name == name);
+                if name.is_empty() || taken {
+                    log!("palette name invalid: {}", name);
+                } else {
+                    custom[index].name = name.clone();
+                    self.p.color_scheme = name;
+                }
+            }
+            PaletteMsg::Delete => {
+                custom.remove(index);
+                // select the neighbouring custom palette, if any is left
+                self.p.color_scheme = if custom.is_empty() {
+                    ModelProperties::default().color_scheme
+                } else {
+                    custom[index.saturating_sub(1)].name.clone()
+                };
+            }
+            PaletteMsg::MoveUp => {
+                if index > 0 {
+                    custom.swap(index, index - 1);
+                }
+            }
+            PaletteMsg::MoveDown => {
+                if index + 1 < custom.len() {
+                    custom.swap(index, index + 1);
+                }
+            }
+            PaletteMsg::UpdateColor(i, color) => match palettes::validate_color(&color) {
+                Ok(()) => custom[index].colors[i] = color,
+                Err(e) => log!("{}", e),
+            },
+            PaletteMsg::AddColor => {
+                let colors = &mut custom[index].colors;
+                colors.push(
+                    colors
+                        .last()
+                        .cloned()
+                        .unwrap_or_else(|| "#000000".to_owned()),
+                );
+            }
+            PaletteMsg::RemoveColor(i) => {
+                if custom[index].colors.len() > 1 {
+                    custom[index].colors.remove(i);
+                }
+            }
+        }
+    }
+
+    fn render_mode_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <select name="mode" id="mode" onchange={ctx.link().callback(|e: Event|{
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateMode(select.value().parse().unwrap())
+            })}>
+            {{
+                let modes = vec![
+                    Mode::Squares(Default::default()).to_string(),
+                    Mode::Strings(Default::default()).to_string(),
+                    Mode::Maze(Default::default()).to_string(),
+                ];
+                modes.into_iter().map(|mode_name|{
+                    html!{
+                        <option value={mode_name.to_string()} selected={self.p.mode.to_string() == mode_name.clone()}>{mode_name}</option>
+                    }
+                }).collect::<Html>()
+            }}
+            </select>
+        }
+    }
+
+    fn render_seed_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="input-group input-group-sm justify-content-center">
+                <input
+                    type="text"
+                    class="form-control"
+                    style="max-width:15em"
+                    id="seed"
+                    value={self.p.seed.to_string()}
+                    onchange={ctx.link().callback(|e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        Msg::UpdateSeed(input.value())
+                    })}/>
+                <div class="input-group-append">
+                    <div class="input-group-text">
+                        <input
+                            type="checkbox"
+                            id="seed_locked"
+                            checked={self.p.seed_locked}
+                            onclick={ctx.link().callback(|_| Msg::ToggleSeedLock)}/>
+                        <label for="seed_locked" style="margin:0 0 0 0.3em">{"lock"}</label>
+                    </div>
+                    <button
+                        type="button"
+                        class="btn btn-secondary"
+                        onclick={ctx.link().callback(|_| Msg::RerollSeed)}>
+                        {"new seed"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_export_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <>
+            <button
+                type="button"
+                class="btn btn-secondary btn-sm"
+                onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::Svg))}>
+                {"Export SVG"}
+            </button>
+            <button
+                type="button"
+                class="btn btn-secondary btn-sm"
+                style="margin-left:0.5em"
+                onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::PlotterSvg))}>
+                {"Export for plotter"}
+            </button>
+            </>
+        }
+    }
+
+    fn render_png_options(&self, ctx: &Context<Self>) -> Html {
+        let parse_pixels = |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            input.value().parse::<u32>().ok().filter(|v| *v > 0)
+        };
+        html! {
+            <div class="input-group input-group-sm justify-content-center">
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:6em"
+                    min="1"
+                    id="png_width"
+                    value={self.export.png_width.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        parse_pixels(e).map(|v| Msg::Export(ExportMsg::UpdatePngWidth(v)))
+                    })}/>
+                <div class="input-group-prepend input-group-append">
+                    <span class="input-group-text">{"×"}</span>
+                </div>
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:6em"
+                    min="1"
+                    id="png_height"
+                    value={self.export.png_height.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        parse_pixels(e).map(|v| Msg::Export(ExportMsg::UpdatePngHeight(v)))
+                    })}/>
+                <div class="input-group-append">
+                    <div class="input-group-text">
+                        <input
+                            type="checkbox"
+                            id="png_transparent"
+                            checked={self.export.png_transparent}
+                            onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::TogglePngTransparent))}/>
+                        <label for="png_transparent" style="margin:0 0 0 0.3em">{"transparent"}</label>
+                    </div>
+                    <button
+                        type="button"
+                        class="btn btn-secondary"
+                        onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::Png))}>
+                        {"Export PNG"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_machine_options(&self, ctx: &Context<Self>) -> Html {
+        let parse_positive = |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            input.value().parse::<u32>().ok().filter(|v| *v > 0)
+        };
+        let machine = &self.export.machine;
+        html! {
+            <div class="input-group input-group-sm justify-content-center">
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:5em"
+                    min="1"
+                    id="width_mm"
+                    value={machine.width_mm.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        parse_positive(e).map(|v| Msg::Export(ExportMsg::UpdateWidthMm(v)))
+                    })}/>
+                <div class="input-group-prepend input-group-append">
+                    <span class="input-group-text">{"×"}</span>
+                </div>
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:5em"
+                    min="1"
+                    id="height_mm"
+                    value={machine.height_mm.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        parse_positive(e).map(|v| Msg::Export(ExportMsg::UpdateHeightMm(v)))
+                    })}/>
+                <div class="input-group-prepend input-group-append">
+                    <span class="input-group-text">{"mm at"}</span>
+                </div>
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:6em"
+                    min="1"
+                    id="feed_rate"
+                    value={machine.feed_rate.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        parse_positive(e).map(|v| Msg::Export(ExportMsg::UpdateFeedRate(v)))
+                    })}/>
+                <div class="input-group-append">
+                    <span class="input-group-text">{"mm/min"}</span>
+                    <button
+                        type="button"
+                        class="btn btn-secondary"
+                        onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::Gcode))}>
+                        {"Export G-code"}
+                    </button>
+                    <button
+                        type="button"
+                        class="btn btn-secondary"
+                        onclick={ctx.link().callback(|_| Msg::Export(ExportMsg::Hpgl))}>
+                        {"Export HPGL"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn update_export(&mut self, msg: ExportMsg) -> bool {
+        match msg {
+            ExportMsg::Svg => {
+                let svg = crate::svg::to_svg(&crate::generate(&self.p));
+                download(&self.export_name("svg"), svg.as_bytes(), "image/svg+xml");
+                false
+            }
+            ExportMsg::PlotterSvg => {
+                let svg = crate::plotter::to_svg(&crate::generate(&self.p));
+                download(
+                    &self.export_name("plotter.svg"),
+                    svg.as_bytes(),
+                    "image/svg+xml",
+                );
+                false
+            }
+            ExportMsg::Png => {
+                let scene = crate::generate(&self.p);
+                match crate::raster::to_png(
+                    &scene,
+                    self.export.png_width,
+                    self.export.png_height,
+                    self.export.png_transparent,
+                ) {
+                    Ok(png) => download(&self.export_name("png"), &png, "image/png"),
+                    Err(e) => log!("PNG export failed: {}", e),
+                }
+                false
+            }
+            ExportMsg::UpdatePngWidth(width) => {
+                self.export.png_width = width;
+                true
+            }
+            ExportMsg::UpdatePngHeight(height) => {
+                self.export.png_height = height;
+                true
+            }
+            ExportMsg::TogglePngTransparent => {
+                self.export.png_transparent = !self.export.png_transparent;
+                true
+            }
+            ExportMsg::Gcode => {
+                let scene = crate::generate(&self.p);
+                let gcode = crate::machine::to_gcode(&scene, &self.export.machine);
+                download(&self.export_name("gcode"), gcode.as_bytes(), "text/plain");
+                false
+            }
+            ExportMsg::Hpgl => {
+                let scene = crate::generate(&self.p);
+                let hpgl = crate::machine::to_hpgl(&scene, &self.export.machine);
+                download(&self.export_name("hpgl"), hpgl.as_bytes(), "text/plain");
+                false
+            }
+            ExportMsg::UpdateWidthMm(width) => {
+                self.export.machine.width_mm = width as f32;
+                true
+            }
+            ExportMsg::UpdateHeightMm(height) => {
+                self.export.machine.height_mm = height as f32;
+                true
+            }
+            ExportMsg::UpdateFeedRate(feed_rate) => {
+                self.export.machine.feed_rate = feed_rate as f32;
+                true
+            }
+        }
+    }
+
+    /// File name for exports, e.g. `genny-squares-1234.svg`.
+    fn export_name(&self, extension: &str) -> String {
+        format!(
+            "genny-{}-{}.{}",
+            self.p.mode.to_string().to_lowercase(),
+            self.p.seed,
+            extension
+        )
+    }
+
+    fn render_variant_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <select name="variants" id="variants" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateVariant(select.value())
+            })}>
+            {{
+                let variants:Vec<String> = vec![Variant::Filled.to_string(), Variant::Outline.to_string(), Variant::Contour.to_string()];
+                variants.iter().map(|variant|{
+                    html!{<option value={variant.clone()} selected={self.p.variant.to_string() == *variant}>{variant}</option>}
+                }).collect::<Html>()
+            }}
+            </select>
+        }
+    }
+
+    fn render_size_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <select name="sizes" id="sizes" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateSize(select.value().parse().unwrap())
+            })}>
+            {{
+                let sizes:Vec<String> = vec![Size::Small.to_string(), Size::Medium.to_string(), Size::Large.to_string()];
+                sizes.iter().map(|size|{
+                    html!{<option value={size.clone()} selected={self.p.size.to_string() == *size}>{size}</option>}
+                }).collect::<Html>()
+            }}
+            </select>
+        }
+    }
+
+    fn render_strings_radius_options(&self, ctx: &Context<Self>) -> Html {
+        let (inner, outer) = match &self.p.mode {
+            Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+            Mode::Strings(props) => (props.inner.clone(), props.outer.clone()),
+        };
+        // exact sizes are in canvas units, from the center to the edge
+        let half = self.p.width() as f32 / 2.0;
+        let radius = inner.reach(&self.p);
+        let margin = half - outer.reach(&self.p);
+        html! {
+            <div class="input-group" style="margin-bottom:1em">
+            <label for="radius-options" style="width:100%; text-align:center">
+                {"Inner size:"}
+            </label>
+            <div class="btn-group btn-group-sm" role="group" aria-label="Radius options" id="radius-options" style="width:100%">
+            {{
+                let sizes = [Size::Small, Size::Medium, Size::Large];
+                sizes.iter().map(|size|{
+                    let scale = StringEnd::radius_preset(*size) / half;
+                    let active = if inner.scale == scale {"active"} else { "" };
+                    let klass = format!("btn btn-secondary {}", active);
+                    html!{
+                        <button
+                            type="button"
+                            class={klass}
+                            onclick={ctx.link().callback(move |_| {
+                                Msg::UpdateStringsRadius(scale)
+                            })}>
+                                {size.to_string()}
+                        </button>
+                    }
+                }).collect::<Html>()
+            }}
+            </div>
+            <div class="input-group input-group-sm" style="margin-top:0.5em">
+                <input
+                    type="number"
+                    class="form-control"
+                    min="0"
+                    step="1"
+                    id="strings_radius"
+                    title="inner size"
+                    value={format!("{:.0}", radius)}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::UpdateStringsRadius(v.max(0.0) / half))
+                    })}/>
+                <input
+                    type="number"
+                    class="form-control"
+                    min="0"
+                    step="1"
+                    id="strings_margin"
+                    title="margin"
+                    value={format!("{:.0}", margin)}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|m| {
+                            Msg::StringEnd(End::Outer, StringEndMsg::Scale((half - m).max(0.0) / half))
+                        })
+                    })}/>
+            </div>
+            <label for="strings_margin" style="width:100%; text-align:center">
+                {"size and margin, in canvas units"}
+            </label>
+            </div>
+        }
+    }
+
+    fn render_string_end_options(&self, ctx: &Context<Self>, end: End) -> Html {
+        let current = match (&self.p.mode, end) {
+            (Mode::Squares(_) | Mode::Maze(_), _) => unreachable!(),
+            (Mode::Strings(props), End::Inner) => props.inner.clone(),
+            (Mode::Strings(props), End::Outer) => props.outer.clone(),
+        };
+        let name = current.shape.name();
+        // what follows the name, like "5,0.5" for a star
+        let settings = current
+            .shape
+            .to_string()
+            .split_once(':')
+            .map_or(String::new(), |(_, settings)| settings.to_owned());
+        let number = |id: &str, value: f32, step: &str, msg: Box<dyn Fn(f32) -> StringEndMsg>| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    step={step.to_owned()}
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::StringEnd(end, msg(v)))
+                    })}/>
+            }
+        };
+        let prefix = match end {
+            End::Inner => "inner",
+            End::Outer => "outer",
+        };
+        let (x, y) = current.center;
+        html! {
+            <>
+            <select name={format!("{}_shape", prefix)} onchange={ctx.link().callback(move |e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::StringEnd(end, StringEndMsg::Shape(select.value().parse().unwrap()))
+            })}>
+            {
+                StringShape::ALL.iter().map(|shape| html! {
+                    <option value={shape.name()} selected={name == shape.name()}>
+                        {shape.name()}
+                    </option>
+                }).collect::<Html>()
+            }
+            </select>
+            {
+                if current.shape == StringShape::Circle {
+                    html! {}
+                } else {
+                    html! {
+                        <input
+                            type="text"
+                            class="form-control form-control-sm"
+                            id={format!("{}_settings", prefix)}
+                            title="shape settings"
+                            value={settings}
+                            onchange={ctx.link().batch_callback(move |e: Event| {
+                                let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                format!("{}:{}", name, input.value())
+                                    .parse()
+                                    .ok()
+                                    .map(|shape| Msg::StringEnd(end, StringEndMsg::Shape(shape)))
+                            })}/>
+                    }
+                }
+            }
+            <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                { number(&format!("{}_x", prefix), x, "0.05", Box::new(move |v| StringEndMsg::Center(v, y))) }
+                { number(&format!("{}_y", prefix), y, "0.05", Box::new(move |v| StringEndMsg::Center(x, v))) }
+                { number(&format!("{}_rotation", prefix), current.rotation, "15", Box::new(StringEndMsg::Rotation)) }
+                { number(&format!("{}_scale", prefix), current.scale, "0.05", Box::new(|v| StringEndMsg::Scale(v.max(0.0)))) }
+            </div>
+            </>
+        }
+    }
+
+    fn render_curve_options(&self, ctx: &Context<Self>) -> Html {
+        let curve = match &self.p.mode {
+            Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+            Mode::Strings(props) => props.curve,
+        };
+        let number = |id: &str, value: f32, step: &str, msg: Box<dyn Fn(f32) -> CurveMsg>| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    step={step.to_owned()}
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::Curve(msg(v)))
+                    })}/>
+            }
+        };
+        let (x, y) = curve.attractor;
+        html! {
+            <>
+            <select name="bend" id="bend" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::Curve(CurveMsg::Bend(select.value().parse().unwrap()))
+            })}>
+            {
+                Bend::ALL.iter().map(|bend| html! {
+                    <option value={bend.to_string()} selected={curve.bend == *bend}>
+                        {bend.to_string()}
+                    </option>
+                }).collect::<Html>()
+            }
+            </select>
+            {
+                if curve.bend == Bend::Straight {
+                    html! {}
+                } else {
+                    html! {
+                        <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                            <div class="input-group-prepend">
+                                <label class="input-group-text" for="cubic">
+                                    <input
+                                        type="checkbox"
+                                        id="cubic"
+                                        checked={curve.cubic}
+                                        onchange={ctx.link().callback(|e: Event| {
+                                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                            Msg::Curve(CurveMsg::Cubic(input.checked()))
+                                        })}/>
+                                    {" S"}
+                                </label>
+                            </div>
+                            { number("bend_strength", curve.strength, "0.1", Box::new(CurveMsg::Strength)) }
+                            {
+                                match curve.bend {
+                                    Bend::Attractor => html! {
+                                        <>
+                                        { number("attractor_x", x, "0.05", Box::new(move |v| CurveMsg::Attractor(v, y))) }
+                                        { number("attractor_y", y, "0.05", Box::new(move |v| CurveMsg::Attractor(x, v))) }
+                                        </>
+                                    },
+                                    Bend::Noise => number("bend_frequency", curve.frequency, "0.5", Box::new(|v| CurveMsg::Frequency(v.max(0.1)))),
+                                    Bend::Straight | Bend::Twist => html! {},
+                                }
+                            }
+                        </div>
+                    }
+                }
+            }
+            </>
+        }
+    }
+
+    fn render_layers_options(&self, ctx: &Context<Self>) -> Html {
+        let (splits, layers) = match &self.p.mode {
+            Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+            Mode::Strings(props) => (props.splits, props.layers.clone()),
+        };
+        let layer = |(i, layer): (usize, &StringLayer)| {
+            let update = move |msg: LayerMsg| Msg::Layers(LayersMsg::Update(i, msg));
+            let number = |id: &str, value: f32, min: &str, max: &str, msg: fn(f32) -> LayerMsg| {
+                html! {
+                    <input
+                        type="number"
+                        class="form-control"
+                        style="max-width:4.5em"
+                        min={min.to_owned()}
+                        max={max.to_owned()}
+                        step="0.1"
+                        id={format!("layer_{}_{}", i, id)}
+                        title={id.replace('_', " ")}
+                        value={value.to_string()}
+                        onchange={ctx.link().batch_callback(move |e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            input.value().parse::<f32>().ok().map(|v| update(msg(v)))
+                        })}/>
+                }
+            };
+            html! {
+                <div style="margin-bottom:1em">
+                    <label for={format!("layer_{}_aperture", i)} style="width:100%; text-align:center">
+                        {format!("Layer {} aperture:", i + 1)}
+                    </label>
+                    <input
+                        type="range"
+                        class="custom-range"
+                        style="width: 100%"
+                        id={format!("layer_{}_aperture", i)}
+                        min="0"
+                        max={splits.saturating_sub(1).to_string()}
+                        value={layer.aperture.to_string()}
+                        onchange={ctx.link().callback(move |e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            update(LayerMsg::Aperture(input.value().parse().unwrap()))
+                        })}/>
+                    <div class="input-group input-group-sm justify-content-center">
+                        <select name={format!("layer_{}_direction", i)} onchange={ctx.link().callback(move |e: Event| {
+                            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                            update(LayerMsg::Direction(select.value().parse().unwrap()))
+                        })}>
+                        {
+                            StringDirection::ALL.iter().map(|direction| html! {
+                                <option value={direction.to_string()} selected={layer.direction == *direction}>
+                                    {direction.to_string()}
+                                </option>
+                            }).collect::<Html>()
+                        }
+                        </select>
+                        <select name={format!("layer_{}_blend", i)} onchange={ctx.link().callback(move |e: Event| {
+                            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                            update(LayerMsg::Blend(select.value().parse().unwrap()))
+                        })}>
+                        {
+                            BlendMode::ALL.iter().map(|blend| html! {
+                                <option value={blend.to_string()} selected={layer.blend == *blend}>
+                                    {blend.to_string()}
+                                </option>
+                            }).collect::<Html>()
+                        }
+                        </select>
+                    </div>
+                    <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                        // a position in the palette, or two for a gradient
+                        <input
+                            type="text"
+                            class="form-control"
+                            style="max-width:5em"
+                            id={format!("layer_{}_color", i)}
+                            title="color: 0 to 1 along the palette, or from-to for a gradient"
+                            value={layer.color.to_string()}
+                            onchange={ctx.link().batch_callback(move |e: Event| {
+                                let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                input.value().parse().ok().map(|color| update(LayerMsg::Color(color)))
+                            })}/>
+                        { number("stroke_width", layer.stroke_width, "0.1", "5", |v| LayerMsg::StrokeWidth(v.max(0.01))) }
+                        { number("opacity", layer.opacity, "0", "1", |v| LayerMsg::Opacity(v.clamp(0.0, 1.0))) }
+                        <button
+                            type="button"
+                            class="btn btn-outline-secondary"
+                            title="remove layer"
+                            onclick={ctx.link().callback(move |_| Msg::Layers(LayersMsg::Remove(i)))}>
+                            {"✕"}
+                        </button>
+                    </div>
+                </div>
+            }
+        };
+        html! {
+            <div class="row text-center">
+                <div class="col">
+                    { layers.iter().enumerate().map(layer).collect::<Html>() }
+                    <button
+                        type="button"
+                        class="btn btn-secondary btn-sm"
+                        onclick={ctx.link().callback(|_| Msg::Layers(LayersMsg::Add))}>
+                        {"Add layer"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_strings_splits_options(&self, ctx: &Context<Self>) -> Html {
+        let (current_splits, show_base) = match &self.p.mode {
+            Mode::Squares(_) | Mode::Maze(_) => unreachable!(),
+            Mode::Strings(props) => (props.splits, props.show_base),
+        };
+        html! {
+            <div class="input-group" style="margin-bottom:1em">
+            <label for="splits-options" style="width:100%; text-align:center">
+                {"Number of lines:"}
+            </label>
+            <div class="btn-group btn-group-sm" role="group" aria-label="Splits options" id="splits-options" style="width:100%">
+            {{
+                HowMany::ALL.iter().map(|how_many|{
+                    let splits = how_many.splits();
+                    let active = if current_splits == splits {"active"} else { "" };
+                    let klass = format!("btn btn-secondary {}", active);
+                    html!{
+                        <button
+                            type="button"
+                            class={klass}
+                            onclick={ctx.link().callback(move |_| {
+                                Msg::UpdateStringsSplits(splits)
+                            })}>
+                                {how_many.to_string()}
+                        </button>
+                    }
+                }).collect::<Html>()
+            }}
+            </div>
+            <input
+                type="number"
+                class="form-control form-control-sm"
+                style="margin-top:0.5em"
+                min="1"
+                step="1"
+                id="strings_splits"
+                title="number of lines"
+                value={current_splits.to_string()}
+                onchange={ctx.link().batch_callback(|e: Event| {
+                    let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                    input.value().parse::<usize>().ok().map(|v| Msg::UpdateStringsSplits(v.max(1)))
+                })}/>
+            <div style="width:100%; text-align:center; margin-top:0.5em">
+                <input
+                    type="checkbox"
+                    id="strings_show_base"
+                    checked={show_base}
+                    onclick={ctx.link().callback(|_| Msg::ToggleStringsBase)}/>
+                <label for="strings_show_base" style="margin:0 0 0 0.3em">{"Show construction guides"}</label>
+            </div>
+            </div>
+        }
+    }
+
+    fn render_squares_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+        <div class="col-sm-3">
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose theme: " }
+                    <br/>
+                    {
+                        self.render_color_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose variant: " }
+                    <br/>
+                    {
+                        self.render_variant_options(ctx)
+                    }
+                </div>
+            </div>
+            {
+                if self.p.variant == Variant::Contour {
+                    html! {
+                        <div class="row text-center">
+                            <div class="col">
+                                {"Contour rounding and offset: " }
+                                <br/>
+                                {
+                                    self.render_contour_options(ctx)
+                                }
+                            </div>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose size: " }
+                    <br/>
+                    {
+                        self.render_size_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Links: " }
+                    <br/>
+                    {
+                        self.render_link_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Grid: " }
+                    <br/>
+                    {
+                        self.render_tiling_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Cells: " }
+                    <br/>
+                    {
+                        self.render_cells_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Color by: " }
+                    <br/>
+                    {
+                        self.render_coloring_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Color drift: " }
+                    <br/>
+                    {
+                        self.render_drift_options(ctx)
+                    }
+                </div>
+            </div>
+        </div>
+        }
+    }
+
+    fn render_cells_options(&self, ctx: &Context<Self>) -> Html {
+        let cells = self.p.cells;
+        // a cell and its gap fit the canvas at most once
+        let room = self.p.width();
+        let number = |id: &str,
+                      value: f32,
+                      min: &str,
+                      max: usize,
+                      step: &str,
+                      msg: fn(f32) -> CellsMsg| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    min={min.to_owned()}
+                    max={max.to_string()}
+                    step={step.to_owned()}
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().filter(|v| *v >= 0.0).map(|v| Msg::Cells(msg(v)))
+                    })}/>
+            }
+        };
+        html! {
+            <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                { number("cell_size", cells.size as f32, "1", room, "1", |v| CellsMsg::Size((v as usize).max(1))) }
+                { number("gap", cells.gap as f32, "0", room - cells.size, "1", |v| CellsMsg::Gap(v as usize)) }
+                { number("corner_radius", cells.corner_radius, "0", 50, "0.5", CellsMsg::CornerRadius) }
+                { number("stroke_width", cells.stroke_width, "0.1", 50, "0.1", CellsMsg::StrokeWidth) }
+            </div>
+        }
+    }
+
+    fn render_contour_options(&self, ctx: &Context<Self>) -> Html {
+        let contour = match self.p.mode {
+            Mode::Squares(props) => props.contour,
+            Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+        };
+        let number = |id: &str, value: f32, min: &str, msg: fn(f32) -> ContourMsg| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    min={min.to_owned()}
+                    max="20"
+                    step="0.5"
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::Contour(msg(v)))
+                    })}/>
+            }
+        };
+        html! {
+            <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                { number("contour_radius", contour.radius, "0", |v| ContourMsg::Radius(v.max(0.0))) }
+                { number("contour_offset", contour.offset, "-20", ContourMsg::Offset) }
+            </div>
+        }
+    }
+
+    fn render_link_options(&self, ctx: &Context<Self>) -> Html {
+        let links = match self.p.mode {
+            Mode::Squares(props) => props.links,
+            Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+        };
+        // chances are shown as percentages
+        let chance = |id: &str, value: f32, msg: fn(f32) -> LinksMsg| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    min="0"
+                    max="100"
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={(value * 100.0).round().to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::Links(msg(v.clamp(0.0, 100.0) / 100.0)))
+                    })}/>
+            }
+        };
+        html! {
+            <div style="margin-top:0.3em">
+                <div class="input-group input-group-sm justify-content-center">
+                    <div class="input-group-prepend">
+                        <span class="input-group-text">{"→ ↓ %"}</span>
+                    </div>
+                    { chance("link_right", links.right, LinksMsg::Right) }
+                    { chance("link_down", links.down, LinksMsg::Down) }
+                </div>
+                <div class="input-group input-group-sm justify-content-center">
+                    <select name="link_bias" id="link_bias" onchange={ctx.link().callback(|e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        Msg::Links(LinksMsg::Bias(select.value().parse().unwrap()))
+                    })}>
+                    {
+                        LinkBias::ALL.iter().map(|bias| html! {
+                            <option value={bias.to_string()} selected={links.bias == *bias}>
+                                {bias.to_string()}
+                            </option>
+                        }).collect::<Html>()
+                    }
+                    </select>
+                    <input
+                        type="number"
+                        class="form-control"
+                        style="max-width:5em"
+                        min="1"
+                        id="max_cluster_size"
+                        title="largest cluster, empty for no limit"
+                        placeholder="max"
+                        value={links.max_cluster_size.map(|max| max.to_string()).unwrap_or_default()}
+                        onchange={ctx.link().callback(|e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            let max = input.value().parse::<usize>().ok().filter(|max| *max > 0);
+                            Msg::Links(LinksMsg::MaxClusterSize(max))
+                        })}/>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_tiling_options(&self, ctx: &Context<Self>) -> Html {
+        let current = match self.p.mode {
+            Mode::Squares(props) => props.tiling,
+            Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+        };
+        html! {
+            <select name="tiling" id="tiling" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateSquaresTiling(select.value().parse().unwrap())
+            })}>
+            {
+                Tiling::ALL.iter().map(|tiling| html! {
+                    <option value={tiling.to_string()} selected={current == *tiling}>
+                        {tiling.to_string()}
+                    </option>
+                }).collect::<Html>()
+            }
+            </select>
+        }
+    }
+
+    fn render_coloring_options(&self, ctx: &Context<Self>) -> Html {
+        let current = match self.p.mode {
+            Mode::Squares(props) => props.coloring,
+            Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+        };
+        html! {
+            <select name="coloring" id="coloring" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::UpdateSquaresColoring(select.value().parse().unwrap())
+            })}>
+            {
+                ClusterColoring::ALL.iter().map(|coloring| html! {
+                    <option value={coloring.to_string()} selected={current == *coloring}>
+                        {coloring.to_string()}
+                    </option>
+                }).collect::<Html>()
+            }
+            </select>
+        }
+    }
+
+    fn render_drift_options(&self, ctx: &Context<Self>) -> Html {
+        let drift = match self.p.mode {
+            Mode::Squares(props) => props.drift,
+            Mode::Strings(_) | Mode::Maze(_) => unreachable!(),
+        };
+        let number = |id: &str, value: f32, step: &str, max: f32, msg: fn(f32) -> DriftMsg| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4.5em"
+                    min="0"
+                    max={max.to_string()}
+                    step={step.to_owned()}
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<f32>().ok().map(|v| Msg::Drift(msg(v.clamp(0.0, max))))
+                    })}/>
+            }
+        };
+        html! {
+            <div style="margin-top:0.3em">
+                <div class="input-group input-group-sm justify-content-center">
+                    <select name="drift_channel" id="drift_channel" onchange={ctx.link().callback(|e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        Msg::Drift(DriftMsg::Channel(select.value().parse().unwrap()))
+                    })}>
+                    {
+                        LchChannel::ALL.iter().map(|channel| html! {
+                            <option value={channel.to_string()} selected={drift.channel == *channel}>
+                                {channel.to_string()}
+                            </option>
+                        }).collect::<Html>()
+                    }
+                    </select>
+                    { number("drift_amount", drift.amount, "1", 180.0, DriftMsg::Amount) }
+                </div>
+                <div class="input-group input-group-sm justify-content-center">
+                    <select name="drift_noise" id="drift_noise" onchange={ctx.link().callback(|e: Event| {
+                        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        Msg::Drift(DriftMsg::Noise(select.value().parse().unwrap()))
+                    })}>
+                    {
+                        NoiseKind::ALL.iter().map(|noise| html! {
+                            <option value={noise.to_string()} selected={drift.noise == *noise}>
+                                {noise.to_string()}
+                            </option>
+                        }).collect::<Html>()
+                    }
+                    </select>
+                    { number("drift_frequency", drift.frequency, "0.1", 20.0, DriftMsg::Frequency) }
+                    <input
+                        type="number"
+                        class="form-control"
+                        style="max-width:4em"
+                        min="1"
+                        max="8"
+                        id="drift_octaves"
+                        title="octaves"
+                        value={drift.octaves.to_string()}
+                        onchange={ctx.link().batch_callback(|e: Event| {
+                            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                            input.value().parse::<usize>().ok()
+                                .map(|v| Msg::Drift(DriftMsg::Octaves(v.clamp(1, 8))))
+                        })}/>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_maze_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+        <div class="col-sm-3">
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose theme: " }
+                    <br/>
+                    {
+                        self.render_color_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose size: " }
+                    <br/>
+                    {
+                        self.render_size_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Cells: " }
+                    <br/>
+                    {
+                        self.render_cells_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"Algorithm: " }
+                    <br/>
+                    {
+                        self.render_maze_algorithm_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    { self.render_maze_solution_options(ctx) }
+                </div>
+            </div>
+        </div>
+        }
+    }
+
+    fn render_maze_algorithm_options(&self, ctx: &Context<Self>) -> Html {
+        let current = match self.p.mode {
+            Mode::Maze(props) => props.algorithm,
+            Mode::Squares(_) | Mode::Strings(_) => unreachable!(),
+        };
+        html! {
+            <select name="maze_algorithm" id="maze_algorithm" onchange={ctx.link().callback(|e: Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                Msg::Maze(MazeMsg::Algorithm(select.value().parse().unwrap()))
+            })}>
+            {
+                MazeAlgorithm::ALL.iter().map(|algorithm| html! {
+                    <option value={algorithm.to_string()} selected={current == *algorithm}>
+                        {algorithm.to_string()}
+                    </option>
+                }).collect::<Html>()
+            }
+            </select>
+        }
+    }
+
+    /// The solution toggle, and the cells it goes between as column and row.
+    fn render_maze_solution_options(&self, ctx: &Context<Self>) -> Html {
+        let props = match self.p.mode {
+            Mode::Maze(props) => props,
+            Mode::Squares(_) | Mode::Strings(_) => unreachable!(),
+        };
+        let lattice = tiling::lattice(&self.p);
+        let (columns, rows) = (lattice.first().map_or(0, |row| row.len()), lattice.len());
+        let (start_column, start_row) = props.start;
+        let (last_column, last_row) = (columns.saturating_sub(1), rows.saturating_sub(1));
+        let (end_column, end_row) = props.end.unwrap_or((last_column, last_row));
+        let cell = |id: &str, value: usize, max: usize, msg: Box<dyn Fn(usize) -> MazeMsg>| {
+            html! {
+                <input
+                    type="number"
+                    class="form-control"
+                    style="max-width:4em"
+                    min="0"
+                    max={max.to_string()}
+                    id={id.to_owned()}
+                    title={id.replace('_', " ")}
+                    value={value.min(max).to_string()}
+                    onchange={ctx.link().batch_callback(move |e: Event| {
+                        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                        input.value().parse::<usize>().ok().map(|v| Msg::Maze(msg(v.min(max))))
+                    })}/>
+            }
+        };
+        html! {
+            <div class="input-group input-group-sm justify-content-center" style="margin-top:0.3em">
+                <div class="input-group-prepend">
+                    <div class="input-group-text">
+                        <input
+                            type="checkbox"
+                            id="maze_solve"
+                            checked={props.solve}
+                            onclick={ctx.link().callback(|_| Msg::Maze(MazeMsg::ToggleSolve))}/>
+                        <label for="maze_solve" style="margin:0 0 0 0.3em">{"solve"}</label>
+                    </div>
+                </div>
+                // no cells, no way through them to pick
+                if props.solve && columns > 0 && rows > 0 {
+                    { cell("start_column", start_column, last_column, Box::new(move |v| MazeMsg::Start(v, start_row))) }
+                    { cell("start_row", start_row, last_row, Box::new(move |v| MazeMsg::Start(start_column, v))) }
+                    <div class="input-group-prepend input-group-append">
+                        <span class="input-group-text">{"→"}</span>
+                    </div>
+                    { cell("end_column", end_column, last_column, Box::new(move |v| MazeMsg::End(v, end_row))) }
+                    { cell("end_row", end_row, last_row, Box::new(move |v| MazeMsg::End(end_column, v))) }
+                }
+            </div>
+        }
+    }
+
+    fn render_strings_options(&self, ctx: &Context<Self>) -> Html {
+        html! {
+        <div class="col-sm-3">
+            <div class="row text-center">
+                <div class="col">
+                    {"Choose theme: " }
+                    <br/>
+                    {
+                        self.render_color_options(ctx)
+                    }
+                </div>
+            </div>
+            <div class="row">
+            <div class="col">
+            { self.render_strings_radius_options(ctx) }
+            </div>
+            </div>
+            <div class="row text-center">
+                <div class="col">
+                    {"From: "}
+                    { self.render_string_end_options(ctx, End::Inner) }
+                </div>
+            </div>
+            <div class="row text-center" style="margin-bottom:1em">
+                <div class="col">
+                    {"To: "}
+                    { self.render_string_end_options(ctx, End::Outer) }
+                </div>
+            </div>
+            <div class="row">
+            <div class="col">
+            { self.render_strings_splits_options(ctx) }
+            </div>
+            </div>
+            <div class="row text-center" style="margin-bottom:1em">
+                <div class="col">
+                    {"Curve: "}
+                    { self.render_curve_options(ctx) }
+                </div>
+            </div>
+            { self.render_layers_options(ctx) }
+        </div>
+        }
+    }
+}
+
+/// `linked` with the user's own palettes kept, so that opening a permalink
+/// never loses them. A shared palette that clashes with one of them by name
+/// is added under a new name.
+fn keep_palettes(mut linked: ModelProperties, own: &[Palette]) -> ModelProperties {
+    let mut palettes = own.to_vec();
+    for mut shared in std::mem::take(&mut linked.custom_palettes) {
+        if palettes.contains(&shared) {
+            continue;
+        }
+        let mut taken = linked.palettes();
+        taken.extend(palettes.iter().cloned());
+        let name = palettes::unique_name(&shared.name, &taken);
+        if linked.color_scheme == shared.name {
+            linked.color_scheme = name.clone();
+        }
+        shared.name = name;
+        palettes.push(shared);
+    }
+    linked.custom_palettes = palettes;
+    linked
+}
+
+fn render_scene(scene: &Scene) -> Html {
+    html! {
+        <svg
+            viewBox={format!("0 0 {} {}", scene.width, scene.height)}
+            fill="none"
+            xmlns="http://www.w3.org/2000/svg">
+            <defs>
+            {
+                scene.symbols.iter().map(|symbol| {
+                    let shapes = symbol.shapes.iter().map(render_shape).collect::<Html>();
+                    html! { <g id={symbol.id.clone()}>{ shapes }</g> }
+                }).collect::<Html>()
+            }
+            </defs>
+            {
+                match &scene.background {
+                    Some(background) => html! {
+                        <rect
+                            x="0"
+                            y="0"
+                            width={scene.width.to_string()}
+                            height={scene.height.to_string()}
+                            fill={background.clone()}/>
+                    },
+                    None => html! {},
+                }
+            }
+            { scene.shapes.iter().map(render_shape).collect::<Html>() }
+        </svg>
+    }
+}
+
+fn render_shape(shape: &Shape) -> Html {
+    match shape {
+        Shape::Circle { center, r, style } => html! {
+            <circle
+                cx={center.x.to_string()}
+                cy={center.y.to_string()}
+                r={r.to_string()}
+                fill={style.fill.clone()}
+                stroke={style.stroke.clone()}
+                stroke-width={style.stroke_width.map(|w| w.to_string())}/>
+        },
+        Shape::Rect {
+            at,
+            width,
+            height,
+            rx,
+            style,
+        } => html! {
+            <rect
+                x={at.x.to_string()}
+                y={at.y.to_string()}
+                width={width.to_string()}
+                height={height.to_string()}
+                rx={rx.to_string()}
+                ry={rx.to_string()}
+                fill={style.fill.clone()}
+                stroke={style.stroke.clone()}
+                stroke-width={style.stroke_width.map(|w| w.to_string())}/>
+        },
+        Shape::Path { cmds, style } => html! {
+            <path
+                d={path_data(cmds)}
+                fill={style.fill.clone()}
+                stroke={style.stroke.clone()}
+                stroke-width={style.stroke_width.map(|w| w.to_string())}
+                stroke-linecap={linecap(style)}
+                opacity={style.opacity.map(|o| o.to_string())}
+                style={style.blend.map(|b| format!("mix-blend-mode:{}", b.css()))}/>
+        },
+        Shape::Use { symbol, at, style } => html! {
+            <use
+                x={at.x.to_string()}
+                y={at.y.to_string()}
+                href={format!("#{}", symbol)}
+                fill={style.fill.clone()}
+                stroke={style.stroke.clone()}
+                stroke-width={style.stroke_width.map(|w| w.to_string())}/>
+        },
+        Shape::Rotated {
+            degrees,
+            center,
+            shapes,
+        } => html! {
+            <g transform={format!("rotate({},{},{})", degrees, center.x, center.y)}>
+                { shapes.iter().map(render_shape).collect::<Html>() }
+            </g>
+        },
+    }
+}
+
+fn linecap(style: &Style) -> Option<String> {
+    if style.round_caps {
+        Some("round".to_owned())
+    } else {
+        None
+    }
+}
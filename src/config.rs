@@ -0,0 +1,1241 @@
+use crate::migrations;
+use crate::outlines;
+use crate::palettes::{self, generate::PaletteGenerator, Palette};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Everything needed to generate a drawing. The same value is persisted by
+/// the web app and accepted by the headless [`crate::generate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelProperties {
+    pub cells: Cells,
+    pub arrows_enabled: bool,
+    pub paths_enabled: bool,
+    pub circles_enabled: bool,
+    pub color_scheme: String,
+    pub variant: Variant,
+    pub size: Size,
+    pub mode: Mode,
+    pub seed: u64,
+    pub seed_locked: bool,
+    /// Palettes made by the user, listed after the built-in ones.
+    pub custom_palettes: Vec<Palette>,
+    /// Settings of the `random` palette.
+    pub palette_generator: PaletteGenerator,
+    pub version: i32,
+}
+
+impl Default for ModelProperties {
+    fn default() -> Self {
+        Self {
+            version: migrations::VERSION,
+            cells: Default::default(),
+            arrows_enabled: false,
+            paths_enabled: false,
+            circles_enabled: false,
+            variant: Variant::Filled,
+            size: Size::Small,
+            color_scheme: "accented".to_owned(),
+            mode: Default::default(),
+            seed: rand::thread_rng().gen(),
+            seed_locked: false,
+            custom_palettes: vec![],
+            palette_generator: Default::default(),
+        }
+    }
+}
+
+impl ModelProperties {
+    pub fn width(&self) -> usize {
+        let base_size = 170;
+        match self.size {
+            Size::Small => base_size,
+            Size::Medium => base_size * 2,
+            Size::Large => base_size * 4,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.width()
+    }
+
+    /// Distance between neighbouring cells, from one corner to the next.
+    pub fn step(&self) -> usize {
+        (self.cells.size + self.cells.gap).max(1)
+    }
+
+    /// Shrinks cells that don't fit the canvas, so that a cell and its gap
+    /// span it at most once. Larger ones leave no room for a grid.
+    pub fn fit_cells(&mut self) {
+        let width = self.width();
+        self.cells.size = self.cells.size.clamp(1, width);
+        self.cells.gap = self.cells.gap.min(width - self.cells.size);
+    }
+
+    /// Parses properties from JSON, see [`ModelProperties::from_value`].
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid JSON config: {}", e))?;
+        Self::from_value(value)
+    }
+
+    /// Reads properties saved by any version of genny, migrating them to the
+    /// current schema. Any missing field is taken from the defaults so that
+    /// hand-written configs only need what they change. Custom palettes must
+    /// only have `#rrggbb` colors, like the ones made in the app.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, String> {
+        let mut value = migrations::upgrade(value)?;
+        // modes may be given by name alone, with their default settings
+        if let Some(serde_json::Value::String(mode)) = value.get("mode") {
+            value["mode"] = serde_json::json!({ mode.clone(): {} });
+        }
+        let mut merged = serde_json::to_value(ModelProperties::default())
+            .expect("default properties always serialize");
+        merge(&mut merged, value);
+        let mut p: ModelProperties =
+            serde_json::from_value(merged).map_err(|e| format!("Invalid config: {}", e))?;
+        for palette in p.custom_palettes.iter() {
+            for color in palette.colors.iter() {
+                palettes::validate_color(color)
+                    .map_err(|e| format!("Invalid palette {}: {}", palette.name, e))?;
+            }
+        }
+        p.fit_cells();
+        Ok(p)
+    }
+
+    /// Encodes the properties for the fragment of a shareable URL, e.g.
+    /// `https://example.com/#<permalink>`.
+    pub fn to_permalink(&self) -> String {
+        // other custom palettes aren't needed to redraw the piece
+        let mut shared = self.clone();
+        shared
+            .custom_palettes
+            .retain(|palette| palette.name == self.color_scheme);
+        let json = serde_json::to_string(&shared).expect("properties always serialize");
+        let mut out = String::new();
+        for b in json.bytes() {
+            if b.is_ascii_alphanumeric() || b"-._~!$'()*+,;=:@/?".contains(&b) {
+                out.push(b as char);
+            } else {
+                out.push_str(&format!("%{:02X}", b));
+            }
+        }
+        out
+    }
+
+    /// Reads properties back from a permalink, or from a whole URL ending in
+    /// one.
+    pub fn from_permalink(link: &str) -> Result<Self, String> {
+        let encoded = link.rsplit_once('#').map_or(link, |(_, fragment)| fragment);
+        let invalid = || format!("Invalid permalink: {}", link);
+        let mut bytes = vec![];
+        let mut chars = encoded.bytes();
+        while let Some(b) = chars.next() {
+            if b == b'%' {
+                let hex = [
+                    chars.next().ok_or_else(invalid)?,
+                    chars.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            } else {
+                bytes.push(b);
+            }
+        }
+        let json = String::from_utf8(bytes).map_err(|_| invalid())?;
+        Self::from_json(&json)
+    }
+
+    /// Built-in palettes, then the generated one, then the user's own.
+    pub fn palettes(&self) -> Vec<Palette> {
+        let mut all = palettes::builtin();
+        all.push(self.palette_generator.generate());
+        all.extend(self.custom_palettes.iter().cloned());
+        all
+    }
+
+    pub fn palette(&self, name: &str) -> Option<Palette> {
+        self.palettes().into_iter().find(|p| p.name == name)
+    }
+
+    /// The colors of the selected palette, falling back to the default
+    /// palette if the name is unknown.
+    pub fn colors(&self) -> Vec<String> {
+        self.palette(&self.color_scheme)
+            .filter(|p| !p.colors.is_empty())
+            .or_else(|| self.palette(&ModelProperties::default().color_scheme))
+            .expect("default palette always exists")
+            .colors
+    }
+}
+
+fn merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        // a different variant of an enum replaces the default one rather than
+        // merging into it
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay))
+            if is_variant(base) && is_variant(&overlay) && base.keys().ne(overlay.keys()) =>
+        {
+            *base = overlay
+        }
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Whether `object` looks like an enum variant with data, e.g.
+/// `{"Strings": {...}}`.
+fn is_variant(object: &serde_json::Map<String, serde_json::Value>) -> bool {
+    object.len() == 1
+        && object
+            .keys()
+            .all(|k| k.starts_with(|c: char| c.is_ascii_uppercase()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Variant {
+    Outline,
+    Filled,
+    /// One closed path around each cluster, for cutting machines and
+    /// plotters.
+    Contour,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variant::Outline => write!(f, "Outline"),
+            Variant::Filled => write!(f, "Filled"),
+            Variant::Contour => write!(f, "Contour"),
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Outline" {
+            Ok(Variant::Outline)
+        } else if s == "Filled" {
+            Ok(Variant::Filled)
+        } else if s == "Contour" {
+            Ok(Variant::Contour)
+        } else {
+            Err(format!("Could not parse Variant from str: {}", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Mode {
+    Squares(SquaresModeProps),
+    Strings(StringsModeProps),
+    Maze(MazeModeProps),
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Strings(Default::default())
+    }
+}
+
+impl From<String> for Mode {
+    fn from(s: String) -> Self {
+        s.parse().unwrap_or_default()
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Squares" {
+            Ok(Mode::Squares(Default::default()))
+        } else if s == "Strings" {
+            Ok(Mode::Strings(Default::default()))
+        } else if s == "Maze" {
+            Ok(Mode::Maze(Default::default()))
+        } else {
+            Err(format!("Could not parse mode from str: {}", s))
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Squares(_) => write!(f, "Squares"),
+            Mode::Strings(_) => write!(f, "Strings"),
+            Mode::Maze(_) => write!(f, "Maze"),
+        }
+    }
+}
+
+/// How big grid cells are and how they're drawn, in Squares and Maze modes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Cells {
+    /// Side of a square. Hexagons and triangles are sized to tile the same
+    /// rows.
+    pub size: usize,
+    /// Space between neighbouring cells; 0 makes a mosaic.
+    pub gap: usize,
+    /// Rounding of square corners, up to half the size.
+    pub corner_radius: f32,
+    /// Width of the lines of the Outline variant.
+    pub stroke_width: f32,
+}
+
+impl Default for Cells {
+    fn default() -> Self {
+        Self {
+            size: 10,
+            gap: 5,
+            corner_radius: 3.0,
+            stroke_width: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SquaresModeProps {
+    pub tiling: Tiling,
+    pub links: Links,
+    pub contour: Contour,
+    pub coloring: ClusterColoring,
+    pub drift: ColorDrift,
+}
+
+/// The lattice Squares mode lays its cells out on.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Tiling {
+    /// Squares with four neighbours.
+    #[default]
+    Square,
+    /// Hexagons with six neighbours.
+    Hex,
+    /// Triangles with three neighbours.
+    Triangle,
+}
+
+impl Tiling {
+    pub const ALL: [Tiling; 3] = [Tiling::Square, Tiling::Hex, Tiling::Triangle];
+}
+
+impl fmt::Display for Tiling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tiling::Square => write!(f, "Square"),
+            Tiling::Hex => write!(f, "Hex"),
+            Tiling::Triangle => write!(f, "Triangle"),
+        }
+    }
+}
+
+impl FromStr for Tiling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tiling::ALL
+            .into_iter()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| format!("Could not parse tiling from str: {}", s))
+    }
+}
+
+/// How the paths of the Contour variant follow their clusters.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Contour {
+    /// Rounding of the corners, 0 for sharp ones.
+    pub radius: f32,
+    /// How far the path is moved out of its cluster; negative values move
+    /// it in.
+    pub offset: f32,
+}
+
+/// How likely squares are to join their neighbours, from sparse dots to long
+/// snakes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Links {
+    /// Chance of linking to the square on the right, 0 to 1.
+    pub right: f32,
+    /// Chance of linking to the square below, 0 to 1.
+    pub down: f32,
+    pub bias: LinkBias,
+    /// Clusters are split rather than grow past this many squares.
+    pub max_cluster_size: Option<usize>,
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self {
+            right: 1.0 / 3.0,
+            down: 1.0 / 3.0,
+            bias: LinkBias::Uniform,
+            max_cluster_size: None,
+        }
+    }
+}
+
+/// Where on the canvas links are more likely. Biased chances average out to
+/// about the same number of links as [`LinkBias::Uniform`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LinkBias {
+    #[default]
+    Uniform,
+    /// Denser near the center, sparser towards the corners.
+    Center,
+    /// Denser towards the corners, sparser near the center.
+    Edges,
+    /// Denser and sparser patches following a noise field.
+    Noise,
+}
+
+impl LinkBias {
+    pub const ALL: [LinkBias; 4] = [
+        LinkBias::Uniform,
+        LinkBias::Center,
+        LinkBias::Edges,
+        LinkBias::Noise,
+    ];
+}
+
+impl fmt::Display for LinkBias {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkBias::Uniform => write!(f, "Uniform"),
+            LinkBias::Center => write!(f, "Center"),
+            LinkBias::Edges => write!(f, "Edges"),
+            LinkBias::Noise => write!(f, "Noise"),
+        }
+    }
+}
+
+impl FromStr for LinkBias {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LinkBias::ALL
+            .into_iter()
+            .find(|b| b.to_string() == s)
+            .ok_or_else(|| format!("Could not parse link bias from str: {}", s))
+    }
+}
+
+/// How Squares mode picks a palette color for each square.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ClusterColoring {
+    /// By cluster size relative to the largest cluster.
+    #[default]
+    Size,
+    /// Each cluster takes the next palette color in turn.
+    Id,
+    /// Lone squares, straight lines, bent lines and branching clusters each
+    /// get their own color.
+    Shape,
+    /// By how many neighbours the square is linked to.
+    Degree,
+    /// By distance from the center of the canvas.
+    Distance,
+    /// By cluster size, with each color used for about as many clusters.
+    Quantile,
+}
+
+impl ClusterColoring {
+    pub const ALL: [ClusterColoring; 6] = [
+        ClusterColoring::Size,
+        ClusterColoring::Id,
+        ClusterColoring::Shape,
+        ClusterColoring::Degree,
+        ClusterColoring::Distance,
+        ClusterColoring::Quantile,
+    ];
+}
+
+impl fmt::Display for ClusterColoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClusterColoring::Size => write!(f, "Size"),
+            ClusterColoring::Id => write!(f, "Id"),
+            ClusterColoring::Shape => write!(f, "Shape"),
+            ClusterColoring::Degree => write!(f, "Degree"),
+            ClusterColoring::Distance => write!(f, "Distance"),
+            ClusterColoring::Quantile => write!(f, "Quantile"),
+        }
+    }
+}
+
+impl FromStr for ClusterColoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ClusterColoring::ALL
+            .into_iter()
+            .find(|c| c.to_string() == s)
+            .ok_or_else(|| format!("Could not parse coloring from str: {}", s))
+    }
+}
+
+/// Shifts the color of each square by a noise field over the canvas, so that
+/// clusters of the same size vary smoothly across the drawing.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ColorDrift {
+    /// The largest shift: degrees of hue, or units of chroma or lightness.
+    pub amount: f32,
+    /// Noise features across the canvas; higher gives busier drift.
+    pub frequency: f32,
+    /// Layers of noise, each at twice the frequency and half the strength of
+    /// the previous one.
+    pub octaves: usize,
+    pub noise: NoiseKind,
+    pub channel: LchChannel,
+}
+
+impl Default for ColorDrift {
+    fn default() -> Self {
+        Self {
+            amount: 60.0,
+            frequency: 1.0,
+            octaves: 1,
+            noise: NoiseKind::Perlin,
+            channel: LchChannel::Hue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum NoiseKind {
+    Perlin,
+    OpenSimplex,
+    Worley,
+}
+
+impl NoiseKind {
+    pub const ALL: [NoiseKind; 3] = [NoiseKind::Perlin, NoiseKind::OpenSimplex, NoiseKind::Worley];
+}
+
+impl fmt::Display for NoiseKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoiseKind::Perlin => write!(f, "Perlin"),
+            NoiseKind::OpenSimplex => write!(f, "OpenSimplex"),
+            NoiseKind::Worley => write!(f, "Worley"),
+        }
+    }
+}
+
+impl FromStr for NoiseKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Perlin" {
+            Ok(NoiseKind::Perlin)
+        } else if s == "OpenSimplex" {
+            Ok(NoiseKind::OpenSimplex)
+        } else if s == "Worley" {
+            Ok(NoiseKind::Worley)
+        } else {
+            Err(format!("Could not parse noise from str: {}", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LchChannel {
+    Hue,
+    Chroma,
+    Lightness,
+}
+
+impl LchChannel {
+    pub const ALL: [LchChannel; 3] = [LchChannel::Hue, LchChannel::Chroma, LchChannel::Lightness];
+}
+
+impl fmt::Display for LchChannel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LchChannel::Hue => write!(f, "Hue"),
+            LchChannel::Chroma => write!(f, "Chroma"),
+            LchChannel::Lightness => write!(f, "Lightness"),
+        }
+    }
+}
+
+impl FromStr for LchChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Hue" {
+            Ok(LchChannel::Hue)
+        } else if s == "Chroma" {
+            Ok(LchChannel::Chroma)
+        } else if s == "Lightness" {
+            Ok(LchChannel::Lightness)
+        } else {
+            Err(format!("Could not parse channel from str: {}", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MazeModeProps {
+    pub algorithm: MazeAlgorithm,
+    /// Highlight the path from `start` to `end`.
+    pub solve: bool,
+    /// `(column, row)` of the cell the solution starts from.
+    pub start: (usize, usize),
+    /// `(column, row)` of the cell the solution leads to; the bottom right
+    /// cell if unset. Cells past the grid are moved to its edge.
+    pub end: Option<(usize, usize)>,
+}
+
+/// How a maze is carved. All of them make perfect mazes, with exactly one
+/// path between any two cells, but each has its own texture.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum MazeAlgorithm {
+    /// Long winding corridors with few dead ends.
+    #[default]
+    Backtracker,
+    /// Many short dead ends.
+    Kruskal,
+    /// Picked uniformly among all possible mazes, with no bias in texture.
+    Wilson,
+}
+
+impl MazeAlgorithm {
+    pub const ALL: [MazeAlgorithm; 3] = [
+        MazeAlgorithm::Backtracker,
+        MazeAlgorithm::Kruskal,
+        MazeAlgorithm::Wilson,
+    ];
+}
+
+impl fmt::Display for MazeAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeAlgorithm::Backtracker => write!(f, "Backtracker"),
+            MazeAlgorithm::Kruskal => write!(f, "Kruskal"),
+            MazeAlgorithm::Wilson => write!(f, "Wilson"),
+        }
+    }
+}
+
+impl FromStr for MazeAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MazeAlgorithm::ALL
+            .into_iter()
+            .find(|a| a.to_string() == s)
+            .ok_or_else(|| format!("Could not parse maze algorithm from str: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StringsModeProps {
+    /// Points spread along each shape.
+    pub splits: usize,
+    /// The shape strings start from.
+    pub inner: StringEnd,
+    /// The shape strings lead to.
+    pub outer: StringEnd,
+    /// Whether to draw the shapes and their points, as a construction guide.
+    pub show_base: bool,
+    /// Sets of strings, drawn in order.
+    pub layers: Vec<StringLayer>,
+    pub curve: StringCurve,
+}
+
+impl Default for StringsModeProps {
+    fn default() -> Self {
+        Self {
+            splits: HowMany::Lots.splits(),
+            // on the default, small canvas
+            inner: StringEnd {
+                scale: StringEnd::radius_preset(Size::Large) / 85.0,
+                ..Default::default()
+            },
+            outer: StringEnd {
+                shape: StringShape::RoundedRect {
+                    ratio: 1.0,
+                    radius: 0.0,
+                },
+                scale: 0.9,
+                ..Default::default()
+            },
+            show_base: false,
+            layers: vec![
+                StringLayer::default(),
+                StringLayer {
+                    aperture: 30,
+                    direction: StringDirection::Backward,
+                    color: LayerColor::Palette(1.0),
+                    ..Default::default()
+                },
+            ],
+            curve: Default::default(),
+        }
+    }
+}
+
+/// One string zigzagging between every point of the outer shape and a point
+/// of the inner one, drawn as a single path.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StringLayer {
+    /// How many points further along the inner shape each string starts.
+    pub aperture: usize,
+    pub direction: StringDirection,
+    pub color: LayerColor,
+    pub stroke_width: f32,
+    /// 0 to 1.
+    pub opacity: f32,
+    pub blend: BlendMode,
+}
+
+impl Default for StringLayer {
+    fn default() -> Self {
+        Self {
+            aperture: 0,
+            direction: StringDirection::Forward,
+            color: LayerColor::Palette(0.0),
+            stroke_width: 0.1,
+            opacity: 1.0,
+            blend: BlendMode::Normal,
+        }
+    }
+}
+
+/// Comma-separated settings, any of them left out for the default:
+/// `aperture=30,backward,color=0-1,width=0.2,opacity=0.5,blend=Multiply`.
+impl fmt::Display for StringLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "aperture={},{},color={},width={},opacity={},blend={}",
+            self.aperture,
+            self.direction.to_string().to_lowercase(),
+            self.color,
+            self.stroke_width,
+            self.opacity,
+            self.blend
+        )
+    }
+}
+
+impl FromStr for StringLayer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layer = StringLayer::default();
+        for setting in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let invalid = || format!("Invalid layer setting: {}", setting);
+            let (key, value) = setting.split_once('=').unwrap_or((setting, ""));
+            match key {
+                "forward" | "backward" if value.is_empty() => {
+                    layer.direction = key.parse().map_err(|_| invalid())?
+                }
+                "aperture" => layer.aperture = value.parse().map_err(|_| invalid())?,
+                "color" => layer.color = value.parse()?,
+                "width" => {
+                    layer.stroke_width = value
+                        .parse()
+                        .ok()
+                        .filter(|w: &f32| *w > 0.0)
+                        .ok_or_else(invalid)?
+                }
+                "opacity" => {
+                    layer.opacity = value
+                        .parse()
+                        .ok()
+                        .filter(|o| (0.0..=1.0).contains(o))
+                        .ok_or_else(invalid)?
+                }
+                "blend" => layer.blend = value.parse()?,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(layer)
+    }
+}
+
+/// Which way a string goes around the shapes.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum StringDirection {
+    /// Clockwise, like the points of the shapes.
+    #[default]
+    Forward,
+    Backward,
+}
+
+impl StringDirection {
+    pub const ALL: [StringDirection; 2] = [StringDirection::Forward, StringDirection::Backward];
+}
+
+impl fmt::Display for StringDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StringDirection::Forward => write!(f, "Forward"),
+            StringDirection::Backward => write!(f, "Backward"),
+        }
+    }
+}
+
+impl FromStr for StringDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StringDirection::ALL
+            .into_iter()
+            .find(|d| d.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Could not parse direction from str: {}", s))
+    }
+}
+
+/// Where a layer takes its color from in the palette, 0 being the first
+/// color and 1 the last.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum LayerColor {
+    /// The palette color closest to a position.
+    Palette(f32),
+    /// Colors blending through the palette from one position to another
+    /// along the string.
+    Gradient(f32, f32),
+}
+
+impl Default for LayerColor {
+    fn default() -> Self {
+        LayerColor::Palette(0.0)
+    }
+}
+
+/// `0.5` for a palette color, `0-1` for a gradient.
+impl fmt::Display for LayerColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayerColor::Palette(at) => write!(f, "{}", at),
+            LayerColor::Gradient(from, to) => write!(f, "{}-{}", from, to),
+        }
+    }
+}
+
+impl FromStr for LayerColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Could not parse layer color from str: {}", s);
+        let position = |p: &str| {
+            p.trim()
+                .parse()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(invalid)
+        };
+        match s.split_once('-') {
+            Some((from, to)) => Ok(LayerColor::Gradient(position(from)?, position(to)?)),
+            None => Ok(LayerColor::Palette(position(s)?)),
+        }
+    }
+}
+
+/// How a layer mixes with what's drawn under it, as in CSS `mix-blend-mode`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Difference,
+    Exclusion,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 8] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+    ];
+
+    /// The name CSS knows it by.
+    pub fn css(&self) -> String {
+        self.to_string().to_lowercase()
+    }
+}
+
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlendMode::Normal => write!(f, "Normal"),
+            BlendMode::Multiply => write!(f, "Multiply"),
+            BlendMode::Screen => write!(f, "Screen"),
+            BlendMode::Overlay => write!(f, "Overlay"),
+            BlendMode::Darken => write!(f, "Darken"),
+            BlendMode::Lighten => write!(f, "Lighten"),
+            BlendMode::Difference => write!(f, "Difference"),
+            BlendMode::Exclusion => write!(f, "Exclusion"),
+        }
+    }
+}
+
+impl FromStr for BlendMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BlendMode::ALL
+            .into_iter()
+            .find(|b| b.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Could not parse blend mode from str: {}", s))
+    }
+}
+
+/// How strings bend on their way between the shapes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StringCurve {
+    pub bend: Bend,
+    /// Cubic curves have two control points and can bend both ways;
+    /// quadratic ones have one.
+    pub cubic: bool,
+    /// How far control points move, 0 for straight strings.
+    pub strength: f32,
+    /// Where strings are pulled towards, as fractions of the canvas width
+    /// and height.
+    pub attractor: (f32, f32),
+    /// Noise features across the canvas.
+    pub frequency: f32,
+}
+
+impl Default for StringCurve {
+    fn default() -> Self {
+        Self {
+            bend: Bend::Straight,
+            cubic: false,
+            strength: 0.5,
+            attractor: (0.5, 0.5),
+            frequency: 2.0,
+        }
+    }
+}
+
+/// What moves the control points of curved strings away from the straight
+/// line.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Bend {
+    #[default]
+    Straight,
+    /// Pulled towards a point, or pushed away with a negative strength.
+    Attractor,
+    /// Swirled around the center of the canvas.
+    Twist,
+    /// Pushed around by a noise field that follows the seed.
+    Noise,
+}
+
+impl Bend {
+    pub const ALL: [Bend; 4] = [Bend::Straight, Bend::Attractor, Bend::Twist, Bend::Noise];
+}
+
+impl fmt::Display for Bend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bend::Straight => write!(f, "Straight"),
+            Bend::Attractor => write!(f, "Attractor"),
+            Bend::Twist => write!(f, "Twist"),
+            Bend::Noise => write!(f, "Noise"),
+        }
+    }
+}
+
+impl FromStr for Bend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Bend::ALL
+            .into_iter()
+            .find(|b| b.to_string() == s)
+            .ok_or_else(|| format!("Could not parse bend from str: {}", s))
+    }
+}
+
+/// One of the two shapes of Strings mode, and where it's drawn.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StringEnd {
+    pub shape: StringShape,
+    /// Where the center goes, as fractions of the canvas width and height.
+    pub center: (f32, f32),
+    /// Degrees clockwise.
+    pub rotation: f32,
+    /// How far the shape reaches from its center, as a fraction of half the
+    /// canvas width.
+    pub scale: f32,
+}
+
+impl Default for StringEnd {
+    fn default() -> Self {
+        Self {
+            shape: StringShape::Circle,
+            center: (0.5, 0.5),
+            rotation: 0.0,
+            scale: 0.5,
+        }
+    }
+}
+
+impl StringEnd {
+    /// How far the inner circle reaches when picked by size, in canvas units
+    /// whatever the canvas size, like the radius of old settings.
+    pub fn radius_preset(size: Size) -> f32 {
+        match size {
+            Size::Small => 15.0,
+            Size::Medium => 30.0,
+            Size::Large => 45.0,
+        }
+    }
+
+    /// How far the shape reaches from its center, in canvas units.
+    pub fn reach(&self, p: &ModelProperties) -> f32 {
+        self.scale * p.width() as f32 / 2.0
+    }
+}
+
+/// A closed outline for strings to stretch between. Points are spread along
+/// it evenly, clockwise from its first corner, or from the left for round
+/// shapes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum StringShape {
+    Circle,
+    /// `ratio` is the height over the width.
+    Ellipse {
+        ratio: f32,
+    },
+    /// A regular polygon, pointing up.
+    Polygon {
+        sides: usize,
+    },
+    /// `inner` is how far in the inner corners are, relative to the tips.
+    Star {
+        points: usize,
+        inner: f32,
+    },
+    /// `ratio` is the height over the width, `radius` rounds the corners
+    /// relative to half the shorter side.
+    RoundedRect {
+        ratio: f32,
+        radius: f32,
+    },
+    /// |x|ⁿ + |y|ⁿ = 1: a diamond for 1, a circle for 2, and ever squarer
+    /// above.
+    Superellipse {
+        exponent: f32,
+    },
+    /// SVG path data, fit to the scale.
+    Path {
+        d: String,
+    },
+}
+
+impl StringShape {
+    /// Each kind of shape with its usual settings.
+    pub const ALL: [StringShape; 7] = [
+        StringShape::Circle,
+        StringShape::Ellipse { ratio: 0.5 },
+        StringShape::Polygon { sides: 6 },
+        StringShape::Star {
+            points: 5,
+            inner: 0.5,
+        },
+        StringShape::RoundedRect {
+            ratio: 1.0,
+            radius: 0.25,
+        },
+        StringShape::Superellipse { exponent: 4.0 },
+        StringShape::Path { d: String::new() },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringShape::Circle => "Circle",
+            StringShape::Ellipse { .. } => "Ellipse",
+            StringShape::Polygon { .. } => "Polygon",
+            StringShape::Star { .. } => "Star",
+            StringShape::RoundedRect { .. } => "RoundedRect",
+            StringShape::Superellipse { .. } => "Superellipse",
+            StringShape::Path { .. } => "Path",
+        }
+    }
+}
+
+/// `Name` or `Name:settings`, like `Star:5,0.5` or `Path:M 0 0 L 1 0 L 0 1 Z`.
+impl fmt::Display for StringShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            StringShape::Circle => Ok(()),
+            StringShape::Ellipse { ratio } => write!(f, ":{}", ratio),
+            StringShape::Polygon { sides } => write!(f, ":{}", sides),
+            StringShape::Star { points, inner } => write!(f, ":{},{}", points, inner),
+            StringShape::RoundedRect { ratio, radius } => write!(f, ":{},{}", ratio, radius),
+            StringShape::Superellipse { exponent } => write!(f, ":{}", exponent),
+            StringShape::Path { d } => write!(f, ":{}", d),
+        }
+    }
+}
+
+impl FromStr for StringShape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, settings) = s.split_once(':').unwrap_or((s, ""));
+        let shape = StringShape::ALL
+            .into_iter()
+            .find(|shape| shape.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Could not parse shape from str: {}", s))?;
+        if settings.is_empty() {
+            return Ok(shape);
+        }
+        let name = shape.name();
+        let invalid = || format!("Invalid settings for {}: {}", name, settings);
+        if let StringShape::Path { .. } = shape {
+            outlines::parse_path(settings)?;
+            return Ok(StringShape::Path {
+                d: settings.to_owned(),
+            });
+        }
+        let numbers = settings
+            .split(',')
+            .map(|n| n.trim().parse::<f32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<f32>, String>>()?;
+        // counts of corners or tips
+        let count = |n: f32, min: usize| n.fract() == 0.0 && n >= min as f32;
+        let shape = match (shape, &numbers[..]) {
+            (StringShape::Ellipse { .. }, &[ratio]) if ratio > 0.0 => {
+                StringShape::Ellipse { ratio }
+            }
+            (StringShape::Polygon { .. }, &[sides]) if count(sides, 3) => StringShape::Polygon {
+                sides: sides as usize,
+            },
+            (StringShape::Star { .. }, &[points, inner]) if count(points, 2) && inner > 0.0 => {
+                StringShape::Star {
+                    points: points as usize,
+                    inner,
+                }
+            }
+            (StringShape::RoundedRect { .. }, &[ratio, radius])
+                if ratio > 0.0 && (0.0..=1.0).contains(&radius) =>
+            {
+                StringShape::RoundedRect { ratio, radius }
+            }
+            (StringShape::Superellipse { .. }, &[exponent]) if exponent > 0.0 => {
+                StringShape::Superellipse { exponent }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(shape)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Size {
+    Small,
+    Medium,
+    Large,
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Size::Small => write!(f, "S"),
+            Size::Medium => write!(f, "M"),
+            Size::Large => write!(f, "L"),
+        }
+    }
+}
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "S" {
+            Ok(Size::Small)
+        } else if s == "M" {
+            Ok(Size::Medium)
+        } else if s == "L" {
+            Ok(Size::Large)
+        } else {
+            Err(format!("Could not parse size from str: {}", s))
+        }
+    }
+}
+
+/// Quick picks for the number of splits of Strings mode.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum HowMany {
+    Few,
+    Some,
+    Lots,
+}
+
+impl HowMany {
+    pub const ALL: [HowMany; 3] = [HowMany::Few, HowMany::Some, HowMany::Lots];
+
+    pub fn splits(&self) -> usize {
+        match self {
+            HowMany::Few => 40,
+            HowMany::Some => 80,
+            HowMany::Lots => 160,
+        }
+    }
+}
+
+impl fmt::Display for HowMany {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HowMany::Few => write!(f, "Few"),
+            HowMany::Some => write!(f, "Some"),
+            HowMany::Lots => write!(f, "Lots"),
+        }
+    }
+}
+
+impl FromStr for HowMany {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "Few" {
+            Ok(HowMany::Few)
+        } else if s == "Some" {
+            Ok(HowMany::Some)
+        } else if s == "Lots" {
+            Ok(HowMany::Lots)
+        } else {
+            Err(format!("Could not parse how many from str: {}", s))
+        }
+    }
+}
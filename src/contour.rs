@@ -0,0 +1,158 @@
+//! Closed paths around groups of cells, traced from the segments of their
+//! outside.
+
+use crate::scene::{PathCmd, Point};
+use std::collections::HashMap;
+
+/// Points closer than this are the same corner.
+const TOLERANCE: f32 = 0.01;
+
+/// Chains `segments` into closed loops. Segments go clockwise around the
+/// shape, so that every one ends where exactly one other starts; where
+/// several do (cells touching at a corner only), any of them is taken.
+pub(crate) fn trace(segments: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let key = |p: Point| {
+        (
+            (p.x / TOLERANCE).round() as i64,
+            (p.y / TOLERANCE).round() as i64,
+        )
+    };
+    let mut starts: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, (a, _)) in segments.iter().enumerate() {
+        starts.entry(key(*a)).or_default().push(i);
+    }
+    // rounding may put the same corner on either side of a key
+    let next = |p: Point, used: &[bool]| {
+        let (x, y) = key(p);
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+            .filter_map(|k| starts.get(&k))
+            .flatten()
+            .copied()
+            .find(|i| !used[*i] && segments[*i].0.distance_to(&p) < TOLERANCE)
+    };
+    let mut used = vec![false; segments.len()];
+    let mut loops = vec![];
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut points = vec![segments[first].0];
+        let mut end = segments[first].1;
+        while let Some(i) = next(end, &used) {
+            used[i] = true;
+            points.push(segments[i].0);
+            end = segments[i].1;
+        }
+        loops.push(simplify(points));
+    }
+    loops.retain(|points| points.len() > 2);
+    loops
+}
+
+/// Drops the corners that don't turn: repeated points and points in the
+/// middle of a straight line.
+fn simplify(points: Vec<Point>) -> Vec<Point> {
+    let mut result: Vec<Point> = vec![];
+    for p in points {
+        if result
+            .last()
+            .is_some_and(|last| last.distance_to(&p) < TOLERANCE)
+        {
+            continue;
+        }
+        while result.len() > 1 && straight(result[result.len() - 2], result[result.len() - 1], p) {
+            result.pop();
+        }
+        result.push(p);
+    }
+    // the loop goes on from its last point to its first
+    loop {
+        let n = result.len();
+        if n < 3 {
+            break;
+        }
+        if result[n - 1].distance_to(&result[0]) < TOLERANCE
+            || straight(result[n - 2], result[n - 1], result[0])
+        {
+            result.pop();
+        } else if straight(result[n - 1], result[0], result[1]) {
+            result.remove(0);
+        } else {
+            break;
+        }
+    }
+    result
+}
+
+/// Whether `p` is on the way from `a` on to `b`.
+fn straight(a: Point, p: Point, b: Point) -> bool {
+    let (u, v) = ((p.x - a.x, p.y - a.y), (b.x - p.x, b.y - p.y));
+    (u.0 * v.1 - u.1 * v.0).abs() < TOLERANCE && u.0 * v.0 + u.1 * v.1 > 0.0
+}
+
+/// Moves every side of a loop `by` outwards, or inwards when negative.
+pub(crate) fn offset(points: &[Point], by: f32) -> Vec<Point> {
+    if by == 0.0 {
+        return points.to_vec();
+    }
+    let n = points.len();
+    // loops go clockwise, so the outside is on the left
+    let normal = |a: Point, b: Point| {
+        let length = a.distance_to(&b);
+        ((b.y - a.y) / length, -(b.x - a.x) / length)
+    };
+    (0..n)
+        .map(|i| {
+            let (prev, p, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+            let (n1, n2) = (normal(prev, p), normal(p, next));
+            // the miter: along the bisector, far enough to keep both sides
+            // `by` away
+            let k = 1.0 + n1.0 * n2.0 + n1.1 * n2.1;
+            let (mx, my) = if k < 1e-3 {
+                (n1.0 * by, n1.1 * by)
+            } else {
+                ((n1.0 + n2.0) * by / k, (n1.1 + n2.1) * by / k)
+            };
+            Point::new(p.x + mx, p.y + my)
+        })
+        .collect()
+}
+
+/// A closed path through `points`, with corners rounded by up to `radius`.
+pub(crate) fn path(points: &[Point], radius: f32) -> Vec<PathCmd> {
+    let n = points.len();
+    let mut cmds: Vec<PathCmd> = vec![];
+    // the path starts where the first corner does
+    let go_to = |p: Point, cmds: &mut Vec<PathCmd>| {
+        cmds.push(if cmds.is_empty() {
+            PathCmd::MoveTo(p)
+        } else {
+            PathCmd::LineTo(p)
+        })
+    };
+    for i in 0..n {
+        let (prev, p, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        // never more than half a side, so that neighbouring corners meet
+        let cut = radius
+            .min(p.distance_to(&prev) / 2.0)
+            .min(p.distance_to(&next) / 2.0);
+        if cut <= 0.0 {
+            go_to(p, &mut cmds);
+            continue;
+        }
+        let towards = |q: Point, by: f32| {
+            let length = p.distance_to(&q);
+            Point::new(
+                p.x + (q.x - p.x) * by / length,
+                p.y + (q.y - p.y) * by / length,
+            )
+        };
+        // a quadratic Bézier curve with the corner as control point
+        go_to(towards(prev, cut), &mut cmds);
+        cmds.push(PathCmd::QuadTo(p, towards(next, cut)));
+    }
+    cmds.push(PathCmd::Close);
+    cmds
+}
@@ -0,0 +1,47 @@
+//! Sets of cells that are joined together, merged in near-constant time.
+
+/// A union-find over `0..n`, with union by size and path halving.
+pub(crate) struct DisjointSet {
+    parent: Vec<usize>,
+    /// Number of members, for roots only.
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    /// `n` sets of one member each.
+    pub(crate) fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    /// The member standing for the set of `i`.
+    pub(crate) fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    /// Merges the sets of `a` and `b`. Returns whether they were apart.
+    pub(crate) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        true
+    }
+
+    /// How many members the set of `i` has.
+    pub(crate) fn size(&mut self, i: usize) -> usize {
+        let root = self.find(i);
+        self.size[root]
+    }
+}
@@ -0,0 +1,19 @@
+//! Lets the browser save exported artwork as a file.
+
+use gloo::file::Blob;
+use wasm_bindgen::JsCast;
+
+pub fn download(filename: &str, contents: &[u8], mime_type: &str) {
+    let blob = Blob::new_with_options(contents, Some(mime_type));
+    let url = web_sys::Url::create_object_url_with_blob(blob.as_ref())
+        .expect("failed to create object url");
+    let anchor: web_sys::HtmlAnchorElement = gloo::utils::document()
+        .create_element("a")
+        .expect("failed to create anchor")
+        .dyn_into()
+        .expect("anchor is an HtmlAnchorElement");
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).expect("failed to revoke object url");
+}
@@ -0,0 +1,311 @@
+//! The early flow-field experiments: arrows, circles and paths following the
+//! field. They're not exposed in the UI, but remain toggleable through
+//! [`ModelProperties`].
+
+use crate::config::ModelProperties;
+use crate::scene::{PathCmd, Point, Shape, Style, Symbol};
+use rand::{rngs::StdRng, Rng};
+use std::convert::TryFrom;
+
+/// Distance between arrows, and length of each step of a path. It doesn't
+/// follow the cells of the grid modes.
+const STEP: usize = 15;
+
+struct Circle {
+    p: Point,
+    r: usize,
+}
+
+struct Arrow {
+    p: Point,
+    angle: f32,
+}
+
+impl Arrow {
+    fn draw(&self) -> Shape {
+        Shape::Rotated {
+            degrees: Arrow::rad_to_deg(self.angle),
+            center: self.p,
+            shapes: vec![Shape::Use {
+                symbol: "arrow".to_owned(),
+                at: self.p,
+                style: Style::fill("black"),
+            }],
+        }
+    }
+
+    fn rad_to_deg(rad: f32) -> f32 {
+        -((rad + std::f32::consts::PI * 0.5) * 180.0 / std::f32::consts::PI)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+struct UsizePoint {
+    x: usize,
+    y: usize,
+}
+
+struct Path {
+    items: Vec<Point>,
+}
+
+impl Path {
+    fn draw(&self, color: &str) -> Shape {
+        let cmds = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                if i == 0 {
+                    PathCmd::MoveTo(*item)
+                } else {
+                    PathCmd::LineTo(*item)
+                }
+            })
+            .collect();
+        Shape::Path {
+            cmds,
+            style: Style::stroke(color, 1.0),
+        }
+    }
+}
+
+/// The symbols instanced by [`render_arrows`].
+pub fn symbols() -> Vec<Symbol> {
+    let p = Point::new;
+    vec![Symbol {
+        id: "arrow".to_owned(),
+        shapes: vec![Shape::Path {
+            cmds: vec![
+                PathCmd::MoveTo(p(0.0, 0.0)),
+                PathCmd::LineTo(p(0.0, 10.0)),
+                PathCmd::LineTo(p(-1.0, 9.0)),
+                PathCmd::LineTo(p(1.0, 9.0)),
+                PathCmd::LineTo(p(0.0, 10.0)),
+                PathCmd::Close,
+            ],
+            style: Style::stroke("black", 1.0),
+        }],
+    }]
+}
+
+pub fn render_arrows(p: &ModelProperties) -> Vec<Shape> {
+    (0..p.height() - STEP)
+        .step_by(STEP)
+        .skip(1)
+        .flat_map(|y| render_arrow_line(p, y))
+        .collect()
+}
+
+fn render_arrow_line(p: &ModelProperties, y: usize) -> Vec<Shape> {
+    (0..p.width() - STEP)
+        .step_by(STEP)
+        .skip(1)
+        .map(|x| {
+            Arrow {
+                p: Point::from_usize(x, y),
+                angle: angle_at(p, Point::from_usize(x, y)),
+            }
+            .draw()
+        })
+        .collect()
+}
+
+fn random_point(p: &ModelProperties, rng: &mut StdRng, points: &[UsizePoint]) -> UsizePoint {
+    let mut i = 0;
+
+    loop {
+        let x = rng.gen_range(0..p.width());
+        let y = rng.gen_range(0..p.height());
+        let point = UsizePoint { x, y };
+        if !points.contains(&point) {
+            return point;
+        }
+        i += 1;
+        if i == 10000 {
+            panic!("should never be reached");
+        }
+    }
+}
+
+fn border_point(p: &ModelProperties, i: usize) -> UsizePoint {
+    UsizePoint {
+        x: if i < 2 * p.width() {
+            i % p.width()
+        } else if i < 2 * p.width() + p.height() {
+            0
+        } else {
+            p.width() - 1
+        },
+        y: if i < p.width() {
+            0
+        } else if i < 2 * p.width() {
+            p.height() - 1
+        } else {
+            (i - 2 * p.width()) % p.height()
+        },
+    }
+}
+
+fn gen_random_point(
+    p: &ModelProperties,
+    rng: &mut StdRng,
+    diameter: usize,
+    circles: &[(Circle, &'static str)],
+) -> Point {
+    let mut i = 0;
+    loop {
+        let x = rng.gen_range(0..p.width()) as f32;
+        let y = rng.gen_range(0..p.height()) as f32;
+        let point = Point { x, y };
+        let mut matching_circles = circles
+            .iter()
+            .filter(|(c, _)| in_circle(&point, c, diameter));
+
+        if matching_circles.next().is_none() {
+            return point;
+        }
+        i += 1;
+        if i == 10000 {
+            panic!("should never be reached");
+        }
+    }
+}
+
+fn circles(p: &ModelProperties, rng: &mut StdRng) -> Vec<(Circle, &'static str)> {
+    let num_circles = 100;
+    (0..num_circles).fold(Vec::new(), |mut acc, i| {
+        acc.push(if i < 10 {
+            (
+                Circle {
+                    p: gen_random_point(p, rng, 60, &acc),
+                    r: 50,
+                },
+                "#E4572E",
+            )
+        } else if i < 40 {
+            (
+                Circle {
+                    p: gen_random_point(p, rng, 20, &acc),
+                    r: 20,
+                },
+                "#F3A712",
+            )
+        } else {
+            (
+                Circle {
+                    p: gen_random_point(p, rng, 10, &acc),
+                    r: 10,
+                },
+                "#A8C686",
+            )
+        });
+        acc
+    })
+}
+
+pub fn render_circles(p: &ModelProperties, rng: &mut StdRng) -> Vec<Shape> {
+    circles(p, rng)
+        .into_iter()
+        .map(|(circle, color)| Shape::Circle {
+            center: circle.p,
+            r: circle.r as f32,
+            style: Style::fill(color),
+        })
+        .collect()
+}
+
+pub fn render_paths(p: &ModelProperties, rng: &mut StdRng) -> Vec<Shape> {
+    let num_paths = (0.05 * ((p.width() * p.height()) as f32)) as usize;
+    let mut all_points = Vec::new();
+    let circles = circles(p, rng);
+    let borders = (p.width() + p.height()) * 2;
+    (0..(num_paths + borders)).fold(Vec::new(), |mut acc, i| {
+        let point = if i < borders {
+            border_point(p, i)
+        } else {
+            random_point(p, rng, &all_points)
+        };
+        let item = render_path(p, point);
+        let color = select_path_color(&item, &circles);
+        acc.push(item.draw(color));
+        for point in item.items {
+            let x = point.x as u32;
+            let y = point.y as u32;
+            if x < p.width() as u32 && y < p.height() as u32 {
+                let x = usize::try_from(x).unwrap();
+                let y = usize::try_from(y).unwrap();
+                all_points.push(UsizePoint { x, y });
+            }
+        }
+        acc
+    })
+}
+
+fn select_path_color(item: &Path, circles: &[(Circle, &'static str)]) -> &'static str {
+    let first_item = item.items.first().unwrap();
+    let mut candidates = circles
+        .iter()
+        .filter(|(circle, _color)| in_circle(first_item, circle, 0));
+    match candidates.next() {
+        Some((_, color)) => color,
+        None => "#669BBC",
+    }
+}
+
+fn render_path(p: &ModelProperties, start: UsizePoint) -> Path {
+    let start_point = Point {
+        x: start.x as f32,
+        y: start.y as f32,
+    };
+    let length = (p.width() + p.height()) / 200;
+    let path = Path {
+        items: vec![start_point],
+    };
+    let val = (0..length).fold((path, start_point), |(mut acc, last_point), _i| {
+        let angle = angle_at(p, last_point);
+        let next_point = Point {
+            x: last_point.x + angle.cos() * STEP as f32,
+            y: last_point.y + angle.sin() * STEP as f32,
+        };
+        acc.items.push(next_point);
+        (acc, next_point)
+    });
+    val.0
+}
+
+fn in_circle(p: &Point, c: &Circle, other_radius: usize) -> bool {
+    p.distance_to(&c.p) <= c.r as f32 + other_radius as f32
+}
+
+fn modify_angle_at(p: &ModelProperties, point: Point, angle: f32) -> f32 {
+    let max_effect_point = Point { x: 250.0, y: 250.0 };
+    let distance = point.distance_to(&max_effect_point) * 4.0;
+    let factor = 1.0 / ((distance / p.width() as f32).powf(2.0) + 1.0);
+
+    let cos = angle.cos();
+    let sin = angle.sin();
+    let bias_x = 0.0;
+    let bias_y = 1.0;
+    let new_x = (1.0 - factor) * cos + factor * bias_x;
+    let new_y = (1.0 - factor) * sin + factor * bias_y;
+
+    new_y.atan2(new_x)
+}
+
+fn zero_to_one_flow_field(p: &ModelProperties, point: Point) -> f32 {
+    let height = p.height() as f32;
+    let width = p.width() as f32;
+    let x = width / ((point.x - 0.5 * width) * 0.2 - width)
+        - ((point.x - 0.5 * width) * 2.0 - width * 0.5) / width;
+    let y = point.y * point.y - height * height * 0.7;
+    x * ((y / (height * height)) * 0.5)
+}
+
+fn angle_at(p: &ModelProperties, point: Point) -> f32 {
+    modify_angle_at(
+        p,
+        point,
+        zero_to_one_flow_field(p, point) * std::f32::consts::PI * 2.0,
+    )
+}
@@ -0,0 +1,122 @@
+//! G-code and HPGL output for plotters, lasers and CNC machines, built on the
+//! per-pen [`Layer`]s of the plotter export.
+
+use crate::plotter::{layers, Layer};
+use crate::scene::{Point, Scene};
+use std::fmt::Write;
+
+/// HPGL plotter units per millimetre.
+const HPGL_UNITS_PER_MM: f32 = 40.0;
+
+/// How the drawing maps onto the machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MachineSettings {
+    /// Physical size of the drawing area, in millimetres. The scene is scaled
+    /// to fit and centered, like a PNG export.
+    pub width_mm: f32,
+    pub height_mm: f32,
+    /// Drawing speed in mm/min.
+    pub feed_rate: f32,
+    /// G-code lines that lift and lower the pen (or switch the laser).
+    pub pen_up: String,
+    pub pen_down: String,
+}
+
+impl Default for MachineSettings {
+    fn default() -> Self {
+        Self {
+            width_mm: 200.0,
+            height_mm: 200.0,
+            feed_rate: 3000.0,
+            pen_up: "G0 Z5".to_owned(),
+            pen_down: "G1 Z0".to_owned(),
+        }
+    }
+}
+
+impl MachineSettings {
+    /// Maps a scene point to machine millimetres, with the origin at the
+    /// bottom-left corner and y pointing up.
+    fn to_mm(&self, scene: &Scene, p: Point) -> Point {
+        let scale = (self.width_mm / scene.width).min(self.height_mm / scene.height);
+        let dx = (self.width_mm - scene.width * scale) / 2.0;
+        let dy = (self.height_mm - scene.height * scale) / 2.0;
+        Point::new(dx + p.x * scale, self.height_mm - (dy + p.y * scale))
+    }
+}
+
+/// Writes G-code in millimetres. Each layer is drawn with its own tool: the
+/// machine pauses with `M0` so the pen can be swapped before `T<n>`.
+pub fn to_gcode(scene: &Scene, settings: &MachineSettings) -> String {
+    let mut out = String::new();
+    out.push_str("; genny\n");
+    out.push_str("G21 ; millimetres\n");
+    out.push_str("G90 ; absolute positioning\n");
+    writeln!(out, "{}", settings.pen_up).unwrap();
+    for (i, layer) in non_empty(scene).iter().enumerate() {
+        writeln!(out, "; pen {}: {}", i + 1, layer.color).unwrap();
+        if i > 0 {
+            writeln!(out, "G0 X0 Y0").unwrap();
+            writeln!(out, "M0 ; change pen to {}", layer.color).unwrap();
+        }
+        writeln!(out, "T{}", i + 1).unwrap();
+        for polyline in layer.polylines.iter() {
+            let start = settings.to_mm(scene, polyline[0]);
+            writeln!(out, "G0 X{:.3} Y{:.3}", start.x, start.y).unwrap();
+            writeln!(out, "{}", settings.pen_down).unwrap();
+            writeln!(out, "G1 F{}", settings.feed_rate).unwrap();
+            for p in polyline.iter().skip(1) {
+                let p = settings.to_mm(scene, *p);
+                writeln!(out, "G1 X{:.3} Y{:.3}", p.x, p.y).unwrap();
+            }
+            writeln!(out, "{}", settings.pen_up).unwrap();
+        }
+    }
+    out.push_str("G0 X0 Y0\n");
+    out.push_str("M2\n");
+    out
+}
+
+/// Writes HPGL, selecting pen `n` for the n-th layer. The feed rate becomes
+/// the pen velocity (`VS`, in cm/s).
+pub fn to_hpgl(scene: &Scene, settings: &MachineSettings) -> String {
+    let mut out = String::new();
+    out.push_str("IN;\n");
+    writeln!(out, "VS{};", (settings.feed_rate / 600.0).round().max(1.0)).unwrap();
+    let units = |p: Point| {
+        let p = settings.to_mm(scene, p);
+        (
+            (p.x * HPGL_UNITS_PER_MM).round() as i64,
+            (p.y * HPGL_UNITS_PER_MM).round() as i64,
+        )
+    };
+    for (i, layer) in non_empty(scene).iter().enumerate() {
+        writeln!(out, "SP{};", i + 1).unwrap();
+        for polyline in layer.polylines.iter() {
+            let (x, y) = units(polyline[0]);
+            writeln!(out, "PU{},{};", x, y).unwrap();
+            let points = polyline
+                .iter()
+                .skip(1)
+                .map(|p| {
+                    let (x, y) = units(*p);
+                    format!("{},{}", x, y)
+                })
+                .collect::<Vec<_>>();
+            writeln!(out, "PD{};", points.join(",")).unwrap();
+        }
+    }
+    out.push_str("PU0,0;\nSP0;\n");
+    out
+}
+
+fn non_empty(scene: &Scene) -> Vec<Layer> {
+    layers(scene)
+        .into_iter()
+        .filter(|l| l.polylines.iter().any(|p| p.len() > 1))
+        .map(|mut l| {
+            l.polylines.retain(|p| p.len() > 1);
+            l
+        })
+        .collect()
+}
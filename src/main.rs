@@ -0,0 +1,555 @@
+//! Command-line frontend: generates a drawing and writes it as SVG, PNG,
+//! plotter-ready SVG, G-code or HPGL.
+
+use genny::config::{HowMany, Mode, ModelProperties, Size, StringEnd};
+use genny::machine::MachineSettings;
+use genny::palettes::vision::{self, Deficiency};
+use std::{env, fs, io::Write, process, str::FromStr};
+
+const USAGE: &str = "Usage: genny [OPTIONS]
+
+Generates a genny drawing and writes it as SVG (or PNG with --png).
+
+Options:
+  --config <FILE>     JSON config (the format saved by the web app); missing fields use defaults
+  --permalink <URL>   start from the settings in a link copied from the web app
+  --mode <MODE>       Squares | Strings | Maze
+  --size <SIZE>       S | M | L
+  --palette <NAME>    palette, e.g. accented, bluish, pastel, reddish, tropical, or a custom one
+                      from --config, --permalink or --import-palette
+  --harmony <NAME>    generate the palette: Analogous | Complementary | Triadic | Monochrome
+  --colors <N>        number of colors of the generated palette
+  --lightness <MIN-MAX>
+                      lightness range of the generated palette, 0-100
+  --chroma <MIN-MAX>  chroma range of the generated palette, 0-130
+  --palette-seed <N>  seed of the generated palette; random if omitted
+  --import-palette <FILE>
+                      draw with a palette from a GIMP .gpl, Adobe .ase or hex list file
+  --variant <NAME>    Filled | Outline | Contour (Squares mode)
+  --contour-radius <N>
+                      rounding of the corners of Contour paths (Squares mode, default 0)
+  --contour-offset <N>
+                      move Contour paths out of their clusters, or in when negative (Squares mode)
+  --cell-size <N>     side of a square cell (Squares and Maze modes, default 10)
+  --gap <N>           space between cells, 0 for a mosaic (Squares and Maze modes, default 5)
+  --corner-radius <N> rounding of square corners (Squares and Maze modes, default 3)
+  --stroke-width <N>  width of Outline lines (Squares and Maze modes, default 1)
+  --tiling <NAME>     Square | Hex | Triangle, the grid cells are laid on (Squares mode)
+  --link-right <P>    chance of linking to the square on the right, 0-1 (Squares mode, default 0.33)
+  --link-down <P>     chance of linking to the square below, 0-1 (Squares mode, default 0.33)
+  --link-bias <NAME>  Uniform | Center | Edges | Noise, where links are denser (Squares mode)
+  --max-cluster <N>   split clusters larger than N squares (Squares mode)
+  --coloring <NAME>   Size | Id | Shape | Degree | Distance | Quantile, what picks each
+                      square's color (Squares mode)
+  --drift <N>         largest color shift across the canvas (Squares mode, default 60)
+  --drift-channel <NAME>
+                      Hue | Chroma | Lightness, the LCH channel shifted (Squares mode)
+  --noise <NAME>      Perlin | OpenSimplex | Worley, the noise driving the shift (Squares mode)
+  --drift-frequency <N>
+                      noise features across the canvas (Squares mode, default 1)
+  --drift-octaves <N> layers of finer noise added on top (Squares mode, default 1)
+  --splits <N>        number of points on each shape, or Few | Some | Lots (Strings mode)
+  --radius <N>        reach of the inner shape in canvas units, or S | M | L for 15, 30 or 45
+                      (Strings mode)
+  --margin <N>        room left around the outer shape, in canvas units (Strings mode)
+  --inner <SHAPE>     shape strings start from, default Circle (Strings mode)
+  --outer <SHAPE>     shape strings lead to, default RoundedRect:1,0 (Strings mode); shapes are
+                      Circle, Ellipse:RATIO, Polygon:SIDES, Star:POINTS,INNER,
+                      RoundedRect:RATIO,RADIUS, Superellipse:EXPONENT or Path:SVG_PATH_DATA
+  --inner-at <X,Y>, --outer-at <X,Y>
+                      center of the shape, as fractions of the canvas (Strings mode, default 0.5,0.5)
+  --inner-rotation <DEG>, --outer-rotation <DEG>
+                      clockwise rotation of the shape (Strings mode)
+  --inner-scale <N>, --outer-scale <N>
+                      reach of the shape, as a fraction of half the canvas (Strings mode)
+  --aperture <N>      points the strings of the last layer skip ahead (Strings mode, default 30)
+  --layer <SETTINGS>  add a layer of strings, replacing the default two; comma-separated
+                      aperture=N, forward or backward, color=AT or color=FROM-TO (0-1 along
+                      the palette, a range for a gradient), width=N, opacity=0-1 and
+                      blend=Normal|Multiply|Screen|Overlay|Darken|Lighten|Difference|Exclusion
+                      (Strings mode, repeatable)
+  --show-base         draw the construction shapes (Strings mode)
+  --bend <NAME>       Straight | Attractor | Twist | Noise, what curves the strings (Strings mode)
+  --cubic             bend strings into S curves rather than arcs (Strings mode)
+  --bend-strength <N> how much strings bend, negative to bend the other way (Strings mode,
+                      default 0.5)
+  --attractor <X,Y>   where Attractor pulls strings, as fractions of the canvas (Strings mode,
+                      default 0.5,0.5)
+  --bend-frequency <N>
+                      Noise features across the canvas (Strings mode, default 2)
+  --algorithm <NAME>  Backtracker | Kruskal | Wilson (Maze mode)
+  --solve             highlight the way through the maze (Maze mode)
+  --from <COL,ROW>    cell the solution starts from (Maze mode, default 0,0)
+  --to <COL,ROW>      cell the solution leads to (Maze mode, default bottom right)
+  --seed <N>          seed for the random generator; random if omitted
+  --png <WxH>         write a PNG of that many pixels instead of SVG, e.g. 4000x4000
+  --transparent       leave the PNG background transparent
+  --simulate <NAME>   draw as seen with Protanopia | Deuteranopia | Tritanopia
+  --check-palette     list neighbouring palette colors that are hard to tell apart, and exit
+  --plotter           write an SVG for pen plotters: one layer per color, optimized paths
+  --gcode             write G-code, with a tool change per color
+  --hpgl              write HPGL, with a pen per color
+  --mm <WxH>          physical size in millimetres for --gcode/--hpgl, e.g. 210.5x297
+                      (default 200x200)
+  --feed <MM/MIN>     drawing speed for --gcode/--hpgl (default 3000)
+  --pen-up <GCODE>    command lifting the pen (default \"G0 Z5\")
+  --pen-down <GCODE>  command lowering the pen (default \"G1 Z0\")
+  -o, --output <FILE> where to write the output; stdout if omitted
+  -h, --help          print this help";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        eprintln!("Run `genny --help` for usage.");
+        process::exit(1);
+    }
+}
+
+enum Format {
+    Svg,
+    Png { width: u32, height: u32 },
+    Plotter,
+    Gcode,
+    Hpgl,
+}
+
+struct Output {
+    path: Option<String>,
+    format: Format,
+    transparent: bool,
+    machine: MachineSettings,
+    simulate: Option<Deficiency>,
+    check_palette: bool,
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (p, output) = parse_args(args)?;
+    if output.check_palette {
+        let warnings = vision::analyze(&p.colors());
+        for warning in warnings.iter() {
+            println!("{}", warning);
+        }
+        if warnings.is_empty() {
+            println!(
+                "{}: all neighbouring colors are distinguishable",
+                p.color_scheme
+            );
+        }
+        return Ok(());
+    }
+    let mut scene = genny::generate(&p);
+    if let Some(deficiency) = output.simulate {
+        scene = scene.map_colors(&|c| vision::simulate(c, deficiency));
+    }
+    let bytes = match output.format {
+        Format::Svg => genny::svg::to_svg(&scene).into_bytes(),
+        Format::Png { width, height } => {
+            genny::raster::to_png(&scene, width, height, output.transparent)?
+        }
+        Format::Plotter => genny::plotter::to_svg(&scene).into_bytes(),
+        Format::Gcode => genny::machine::to_gcode(&scene, &output.machine).into_bytes(),
+        Format::Hpgl => genny::machine::to_hpgl(&scene, &output.machine).into_bytes(),
+    };
+    match output.path {
+        Some(path) => {
+            fs::write(&path, bytes).map_err(|e| format!("Could not write {}: {}", path, e))
+        }
+        None => std::io::stdout()
+            .write_all(&bytes)
+            .map_err(|e| format!("Could not write to stdout: {}", e)),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<(ModelProperties, Output), String> {
+    let mut p = match flag_value(args, "--config")? {
+        Some(path) => {
+            let json =
+                fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+            ModelProperties::from_json(&json)?
+        }
+        None => match flag_value(args, "--permalink")? {
+            Some(link) => ModelProperties::from_permalink(&link)?,
+            None => ModelProperties::default(),
+        },
+    };
+    // mode settings may be given before --mode, so pick the mode first
+    if let Some(mode) = flag_value(args, "--mode")? {
+        let mode: Mode = mode.parse()?;
+        // keep the mode's settings from the config when re-selecting it
+        if mode.to_string() != p.mode.to_string() {
+            p.mode = mode;
+        }
+    }
+    let mut output = Output {
+        path: None,
+        format: Format::Svg,
+        transparent: false,
+        machine: MachineSettings::default(),
+        simulate: None,
+        check_palette: false,
+    };
+
+    // the first --layer replaces the layers from the defaults or the config
+    let mut layers_given = false;
+    // sizes in canvas units wait for the canvas size, which may come later
+    let (mut radius, mut margin) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--config" | "--permalink" | "--mode" => {
+                value()?;
+            }
+            "--size" => p.size = value()?.parse()?,
+            "--palette" => {
+                let palette = value()?;
+                if p.palette(&palette).is_none() {
+                    return Err(format!("Unknown palette: {}", palette));
+                }
+                p.color_scheme = palette;
+            }
+            "--harmony" | "--colors" | "--lightness" | "--chroma" | "--palette-seed" => {
+                let generator = &mut p.palette_generator;
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                match arg.as_str() {
+                    "--harmony" => generator.harmony = value.parse()?,
+                    "--colors" => {
+                        generator.count =
+                            value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?
+                    }
+                    "--lightness" => {
+                        generator.lightness = parse_range(&value).ok_or_else(invalid)?
+                    }
+                    "--chroma" => generator.chroma = parse_range(&value).ok_or_else(invalid)?,
+                    _ => generator.seed = value.parse().map_err(|_| invalid())?,
+                }
+                p.color_scheme = genny::palettes::generate::NAME.to_owned();
+            }
+            "--import-palette" => {
+                let path = value()?;
+                let contents =
+                    fs::read(&path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+                let mut palette = genny::palettes::import::parse(&path, &contents)?;
+                palette.name = genny::palettes::unique_name(&palette.name, &p.palettes());
+                p.color_scheme = palette.name.clone();
+                p.custom_palettes.push(palette);
+            }
+            "--variant" => p.variant = value()?.parse()?,
+            "--cell-size" | "--gap" | "--corner-radius" | "--stroke-width" => {
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                let cells = &mut p.cells;
+                match arg.as_str() {
+                    "--cell-size" => {
+                        cells.size = value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?
+                    }
+                    "--gap" => cells.gap = value.parse().map_err(|_| invalid())?,
+                    "--corner-radius" => {
+                        cells.corner_radius = value
+                            .parse()
+                            .ok()
+                            .filter(|r: &f32| *r >= 0.0)
+                            .ok_or_else(invalid)?
+                    }
+                    _ => {
+                        cells.stroke_width = value
+                            .parse()
+                            .ok()
+                            .filter(|w: &f32| *w > 0.0)
+                            .ok_or_else(invalid)?
+                    }
+                }
+            }
+            "--link-right" | "--link-down" | "--link-bias" | "--max-cluster" => {
+                let links = match p.mode {
+                    Mode::Squares(ref mut props) => &mut props.links,
+                    _ => return Err(format!("{} requires --mode Squares", arg)),
+                };
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                let chance = || {
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|c: &f32| (0.0..=1.0).contains(c))
+                        .ok_or_else(invalid)
+                };
+                match arg.as_str() {
+                    "--link-right" => links.right = chance()?,
+                    "--link-down" => links.down = chance()?,
+                    "--link-bias" => links.bias = value.parse()?,
+                    _ => {
+                        links.max_cluster_size =
+                            Some(value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?)
+                    }
+                }
+            }
+            "--contour-radius" | "--contour-offset" => {
+                let contour = match p.mode {
+                    Mode::Squares(ref mut props) => &mut props.contour,
+                    _ => return Err(format!("{} requires --mode Squares", arg)),
+                };
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                let number: f32 = value.parse().map_err(|_| invalid())?;
+                if arg == "--contour-radius" {
+                    contour.radius = Some(number).filter(|r| *r >= 0.0).ok_or_else(invalid)?;
+                } else {
+                    contour.offset = number;
+                }
+            }
+            "--tiling" => match p.mode {
+                Mode::Squares(ref mut props) => props.tiling = value()?.parse()?,
+                _ => return Err(format!("{} requires --mode Squares", arg)),
+            },
+            "--coloring" => match p.mode {
+                Mode::Squares(ref mut props) => props.coloring = value()?.parse()?,
+                _ => return Err(format!("{} requires --mode Squares", arg)),
+            },
+            "--drift" | "--drift-channel" | "--noise" | "--drift-frequency" | "--drift-octaves" => {
+                let drift = match p.mode {
+                    Mode::Squares(ref mut props) => &mut props.drift,
+                    _ => return Err(format!("{} requires --mode Squares", arg)),
+                };
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                match arg.as_str() {
+                    "--drift" => drift.amount = value.parse().map_err(|_| invalid())?,
+                    "--drift-channel" => drift.channel = value.parse()?,
+                    "--noise" => drift.noise = value.parse()?,
+                    "--drift-frequency" => {
+                        drift.frequency = value
+                            .parse()
+                            .ok()
+                            .filter(|f: &f32| *f > 0.0)
+                            .ok_or_else(invalid)?
+                    }
+                    _ => {
+                        drift.octaves = value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?
+                    }
+                }
+            }
+            "--splits" | "--radius" | "--margin" | "--aperture" | "--layer" | "--show-base" => {
+                let props = match p.mode {
+                    Mode::Strings(ref mut props) => props,
+                    _ => return Err(format!("{} requires --mode Strings", arg)),
+                };
+                match arg.as_str() {
+                    "--splits" => {
+                        let value = value()?;
+                        props.splits = match value.parse::<usize>() {
+                            Ok(splits) if splits > 0 => splits,
+                            Ok(_) => return Err(format!("Invalid value for --splits: {}", value)),
+                            Err(_) => value.parse::<HowMany>()?.splits(),
+                        }
+                    }
+                    "--radius" | "--margin" => {
+                        let value = value()?;
+                        let units = value.parse::<f32>().ok().filter(|n| *n >= 0.0);
+                        if arg == "--margin" {
+                            margin =
+                                Some(units.ok_or_else(|| {
+                                    format!("Invalid value for --margin: {}", value)
+                                })?);
+                        } else {
+                            radius = Some(match units {
+                                Some(units) => units,
+                                None => StringEnd::radius_preset(value.parse::<Size>()?),
+                            });
+                        }
+                    }
+                    "--aperture" => {
+                        let aperture = value()?
+                            .parse()
+                            .map_err(|e| format!("Invalid aperture: {}", e))?;
+                        match props.layers.last_mut() {
+                            Some(layer) => layer.aperture = aperture,
+                            None => return Err("--aperture needs a layer".to_owned()),
+                        }
+                    }
+                    "--layer" => {
+                        if !layers_given {
+                            props.layers.clear();
+                            layers_given = true;
+                        }
+                        props.layers.push(value()?.parse()?);
+                    }
+                    _ => props.show_base = true,
+                }
+            }
+            "--inner" | "--outer" | "--inner-at" | "--outer-at" | "--inner-rotation"
+            | "--outer-rotation" | "--inner-scale" | "--outer-scale" => {
+                let props = match p.mode {
+                    Mode::Strings(ref mut props) => props,
+                    _ => return Err(format!("{} requires --mode Strings", arg)),
+                };
+                let end = if arg.starts_with("--inner") {
+                    &mut props.inner
+                } else {
+                    &mut props.outer
+                };
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                match &arg[7..] {
+                    "" => end.shape = value.parse()?,
+                    "-at" => {
+                        let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+                        end.center = (
+                            x.trim().parse().map_err(|_| invalid())?,
+                            y.trim().parse().map_err(|_| invalid())?,
+                        )
+                    }
+                    "-rotation" => end.rotation = value.parse().map_err(|_| invalid())?,
+                    _ => {
+                        end.scale = value
+                            .parse()
+                            .ok()
+                            .filter(|s: &f32| *s >= 0.0)
+                            .ok_or_else(invalid)?;
+                        // the last of --radius and --inner-scale wins
+                        if arg == "--inner-scale" {
+                            radius = None;
+                        }
+                    }
+                }
+            }
+            "--bend" | "--cubic" | "--bend-strength" | "--attractor" | "--bend-frequency" => {
+                let curve = match p.mode {
+                    Mode::Strings(ref mut props) => &mut props.curve,
+                    _ => return Err(format!("{} requires --mode Strings", arg)),
+                };
+                if arg == "--cubic" {
+                    curve.cubic = true;
+                    continue;
+                }
+                let value = value()?;
+                let invalid = || format!("Invalid value for {}: {}", arg, value);
+                match arg.as_str() {
+                    "--bend" => curve.bend = value.parse()?,
+                    "--bend-strength" => curve.strength = value.parse().map_err(|_| invalid())?,
+                    "--attractor" => {
+                        let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+                        curve.attractor = (
+                            x.trim().parse().map_err(|_| invalid())?,
+                            y.trim().parse().map_err(|_| invalid())?,
+                        )
+                    }
+                    _ => {
+                        curve.frequency = value
+                            .parse()
+                            .ok()
+                            .filter(|f: &f32| *f > 0.0)
+                            .ok_or_else(invalid)?
+                    }
+                }
+            }
+            "--algorithm" | "--solve" | "--from" | "--to" => {
+                let props = match p.mode {
+                    Mode::Maze(ref mut props) => props,
+                    _ => return Err(format!("{} requires --mode Maze", arg)),
+                };
+                match arg.as_str() {
+                    "--algorithm" => props.algorithm = value()?.parse()?,
+                    "--solve" => props.solve = true,
+                    "--from" => props.start = parse_cell(&value()?)?,
+                    _ => props.end = Some(parse_cell(&value()?)?),
+                }
+            }
+            "--seed" => {
+                p.seed = value()?
+                    .parse()
+                    .map_err(|e| format!("Invalid seed: {}", e))?;
+                p.seed_locked = true;
+            }
+            "--png" => {
+                let (width, height) = parse_size(&value()?, "PNG size")?;
+                output.format = Format::Png { width, height };
+            }
+            "--transparent" => output.transparent = true,
+            "--simulate" => output.simulate = Some(value()?.parse()?),
+            "--check-palette" => output.check_palette = true,
+            "--plotter" => output.format = Format::Plotter,
+            "--gcode" => output.format = Format::Gcode,
+            "--hpgl" => output.format = Format::Hpgl,
+            "--mm" => {
+                let (width, height) = parse_size(&value()?, "size in millimetres")?;
+                output.machine.width_mm = width;
+                output.machine.height_mm = height;
+            }
+            "--feed" => {
+                let feed = value()?;
+                output.machine.feed_rate = feed
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|f| *f > 0.0)
+                    .ok_or_else(|| format!("Invalid feed rate: {}", feed))?
+            }
+            "--pen-up" => output.machine.pen_up = value()?,
+            "--pen-down" => output.machine.pen_down = value()?,
+            "-o" | "--output" => output.path = Some(value()?),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    p.fit_cells();
+    let half = p.width() as f32 / 2.0;
+    if let Mode::Strings(ref mut props) = p.mode {
+        if let Some(radius) = radius {
+            props.inner.scale = radius / half;
+        }
+        if let Some(margin) = margin {
+            props.outer.scale = (half - margin).max(0.0) / half;
+        }
+    }
+    if !p.seed_locked {
+        eprintln!("seed: {}", p.seed);
+    }
+    Ok((p, output))
+}
+
+/// Two sizes above zero, like `800x600` pixels or `210.5x297` millimetres.
+fn parse_size<T: FromStr + PartialOrd + Default>(s: &str, what: &str) -> Result<(T, T), String> {
+    let invalid = || format!("Invalid {}, expected WIDTHxHEIGHT: {}", what, s);
+    let (width, height) = s.split_once('x').ok_or_else(invalid)?;
+    let side = |side: &str| {
+        side.parse::<T>()
+            .ok()
+            .filter(|n| *n > T::default())
+            .ok_or_else(invalid)
+    };
+    Ok((side(width)?, side(height)?))
+}
+
+fn parse_cell(s: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid cell, expected COLUMN,ROW: {}", s);
+    let (column, row) = s.split_once(',').ok_or_else(invalid)?;
+    Ok((
+        column.trim().parse().map_err(|_| invalid())?,
+        row.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+fn parse_range(s: &str) -> Option<(f32, f32)> {
+    let (min, max) = s.split_once('-')?;
+    Some((min.parse().ok()?, max.parse().ok()?))
+}
+
+fn flag_value(args: &[String], flag: &str) -> Result<Option<String>, String> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => args
+            .get(i + 1)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("Missing value for {}", flag)),
+        None => Ok(None),
+    }
+}
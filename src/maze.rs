@@ -0,0 +1,287 @@
+//! Perfect mazes carved out of the Squares grid: every cell can be reached
+//! from every other one by exactly one path.
+
+use crate::config::{MazeAlgorithm, MazeModeProps, ModelProperties};
+use crate::disjoint_set::DisjointSet;
+use crate::scene::{Shape, Style};
+use crate::tiling;
+use rand::{seq::SliceRandom, Rng};
+
+/// A cell, as `(column, row)`.
+pub type Cell = (usize, usize);
+
+/// Which walls between neighbouring cells have been opened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maze {
+    pub columns: usize,
+    pub rows: usize,
+    /// Open towards the cell on the right, by cell index.
+    right: Vec<bool>,
+    /// Open towards the cell below, by cell index.
+    down: Vec<bool>,
+}
+
+impl Maze {
+    /// A maze of `columns` by `rows` cells, carved with `algorithm`.
+    pub fn carve(
+        columns: usize,
+        rows: usize,
+        algorithm: MazeAlgorithm,
+        rng: &mut impl Rng,
+    ) -> Maze {
+        let mut maze = Maze {
+            columns,
+            rows,
+            right: vec![false; columns * rows],
+            down: vec![false; columns * rows],
+        };
+        if columns > 0 && rows > 0 {
+            match algorithm {
+                MazeAlgorithm::Backtracker => maze.backtracker(rng),
+                MazeAlgorithm::Kruskal => maze.kruskal(rng),
+                MazeAlgorithm::Wilson => maze.wilson(rng),
+            }
+        }
+        maze
+    }
+
+    /// Whether `cell` is open up, left, right and down.
+    pub fn links(&self, (column, row): Cell) -> [bool; 4] {
+        let i = self.index((column, row));
+        [
+            row > 0 && self.down[i - self.columns],
+            column > 0 && self.right[i - 1],
+            self.right[i],
+            self.down[i],
+        ]
+    }
+
+    /// How many walls have been opened.
+    pub fn passages(&self) -> usize {
+        self.right
+            .iter()
+            .chain(self.down.iter())
+            .filter(|open| **open)
+            .count()
+    }
+
+    /// The cells on the way from `from` to `to`, both included.
+    pub fn solve(&self, from: Cell, to: Cell) -> Vec<Cell> {
+        let mut previous: Vec<Option<Cell>> = vec![None; self.columns * self.rows];
+        let mut queue = std::collections::VecDeque::from([from]);
+        previous[self.index(from)] = Some(from);
+        while let Some(cell) = queue.pop_front() {
+            if cell == to {
+                break;
+            }
+            for next in self.open_neighbours(cell) {
+                if previous[self.index(next)].is_none() {
+                    previous[self.index(next)] = Some(cell);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut path = vec![to];
+        let mut cell = to;
+        while cell != from {
+            match previous[self.index(cell)] {
+                Some(p) => cell = p,
+                // unreachable in a perfect maze
+                None => return vec![],
+            }
+            path.push(cell);
+        }
+        path.reverse();
+        path
+    }
+
+    fn index(&self, (column, row): Cell) -> usize {
+        row * self.columns + column
+    }
+
+    fn neighbours(&self, (column, row): Cell) -> Vec<Cell> {
+        let mut result = vec![];
+        if row > 0 {
+            result.push((column, row - 1));
+        }
+        if column > 0 {
+            result.push((column - 1, row));
+        }
+        if column + 1 < self.columns {
+            result.push((column + 1, row));
+        }
+        if row + 1 < self.rows {
+            result.push((column, row + 1));
+        }
+        result
+    }
+
+    fn open_neighbours(&self, cell: Cell) -> Vec<Cell> {
+        let [up, left, right, down] = self.links(cell);
+        let (column, row) = cell;
+        let mut result = vec![];
+        if up {
+            result.push((column, row - 1));
+        }
+        if left {
+            result.push((column - 1, row));
+        }
+        if right {
+            result.push((column + 1, row));
+        }
+        if down {
+            result.push((column, row + 1));
+        }
+        result
+    }
+
+    /// Opens the wall between two neighbouring cells.
+    fn open(&mut self, a: Cell, b: Cell) {
+        let (first, second) = if self.index(a) < self.index(b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let i = self.index(first);
+        if first.1 == second.1 {
+            self.right[i] = true;
+        } else {
+            self.down[i] = true;
+        }
+    }
+
+    /// Randomized depth-first search: walks to a random unvisited neighbour
+    /// until stuck, then backs up to the last cell that has one.
+    fn backtracker(&mut self, rng: &mut impl Rng) {
+        let mut visited = vec![false; self.columns * self.rows];
+        let start = (rng.gen_range(0..self.columns), rng.gen_range(0..self.rows));
+        visited[self.index(start)] = true;
+        let mut stack = vec![start];
+        while let Some(&cell) = stack.last() {
+            let unvisited: Vec<Cell> = self
+                .neighbours(cell)
+                .into_iter()
+                .filter(|n| !visited[self.index(*n)])
+                .collect();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    self.open(cell, next);
+                    visited[self.index(next)] = true;
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    /// Randomized Kruskal: opens walls in random order whenever they separate
+    /// cells that aren't connected yet.
+    fn kruskal(&mut self, rng: &mut impl Rng) {
+        let mut walls: Vec<(Cell, Cell)> = (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .flat_map(|cell| {
+                let (column, row) = cell;
+                let mut walls = vec![];
+                if column + 1 < self.columns {
+                    walls.push((cell, (column + 1, row)));
+                }
+                if row + 1 < self.rows {
+                    walls.push((cell, (column, row + 1)));
+                }
+                walls
+            })
+            .collect();
+        walls.shuffle(rng);
+        let mut connected = DisjointSet::new(self.columns * self.rows);
+        for (a, b) in walls {
+            if connected.union(self.index(a), self.index(b)) {
+                self.open(a, b);
+            }
+        }
+    }
+
+    /// Wilson's algorithm: loop-erased random walks from each cell outside
+    /// the maze until they run into it.
+    fn wilson(&mut self, rng: &mut impl Rng) {
+        let cells = self.columns * self.rows;
+        let mut in_maze = vec![false; cells];
+        in_maze[rng.gen_range(0..cells)] = true;
+        // where the walk last left each cell; revisiting a cell overwrites
+        // it, which erases the loop
+        let mut next: Vec<Option<Cell>> = vec![None; cells];
+        let mut order: Vec<Cell> = (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .collect();
+        order.shuffle(rng);
+        for start in order {
+            let mut cell = start;
+            while !in_maze[self.index(cell)] {
+                let step = *self
+                    .neighbours(cell)
+                    .choose(rng)
+                    .expect("a maze of more than one cell has neighbours");
+                next[self.index(cell)] = Some(step);
+                cell = step;
+            }
+            let mut cell = start;
+            while !in_maze[self.index(cell)] {
+                in_maze[self.index(cell)] = true;
+                let step = next[self.index(cell)].expect("the walk left every cell on it");
+                self.open(cell, step);
+                cell = step;
+            }
+        }
+    }
+}
+
+pub(crate) fn render_maze(
+    p: &ModelProperties,
+    props: MazeModeProps,
+    rng: &mut impl Rng,
+) -> Vec<Shape> {
+    let lattice = tiling::lattice(p);
+    let rows = lattice.len();
+    let columns = lattice.first().map_or(0, |row| row.len());
+    let maze = Maze::carve(columns, rows, props.algorithm, rng);
+    let colors = p.colors();
+    let style = |color: &String, filled: bool| Style {
+        stroke: Some(color.clone()),
+        fill: filled.then(|| color.clone()),
+        pen: Some(color.clone()),
+        ..Default::default()
+    };
+    let mut shapes = vec![];
+    if props.solve && columns > 0 && rows > 0 {
+        let clamp = |(column, row): Cell| (column.min(columns - 1), row.min(rows - 1));
+        let path = maze.solve(
+            clamp(props.start),
+            clamp(props.end.unwrap_or((columns - 1, rows - 1))),
+        );
+        let highlight = style(colors.last().expect("palettes aren't empty"), true);
+        for (k, &(column, row)) in path.iter().enumerate() {
+            // links only reach right and down, so steps up or left are drawn
+            // from the cell they arrive at
+            let towards = |other: Option<&Cell>, (dc, dr): (usize, usize)| {
+                other == Some(&(column + dc, row + dr))
+            };
+            let neighbours = [path.get(k.wrapping_sub(1)), path.get(k + 1)];
+            let right = neighbours.iter().any(|n| towards(*n, (1, 0)));
+            let down = neighbours.iter().any(|n| towards(*n, (0, 1)));
+            shapes.extend(tiling::filled(
+                lattice[row][column],
+                right,
+                down,
+                &highlight,
+            ));
+        }
+    }
+    let walls = style(&colors[0], false);
+    for (row, points) in lattice.iter().enumerate() {
+        for (column, at) in points.iter().enumerate() {
+            shapes.extend(tiling::outline(*at, maze.links((column, row)), &walls));
+        }
+    }
+    shapes
+}
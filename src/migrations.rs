@@ -0,0 +1,181 @@
+//! Upgrades settings saved by older versions of genny (in `LocalStorage`,
+//! permalinks or config files) to the current schema, one version at a time.
+//!
+//! Every change to the shape of [`crate::config::ModelProperties`] bumps
+//! [`VERSION`] and appends a step to [`MIGRATIONS`].
+
+use serde_json::{Map, Value};
+
+/// The schema version written by this build.
+pub const VERSION: i32 = 16;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades version `n + 1` to `n + 2`.
+const MIGRATIONS: [Migration; VERSION as usize - 1] = [
+    v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8, v8_to_v9, v9_to_v10,
+    v10_to_v11, v11_to_v12, v12_to_v13, v13_to_v14, v14_to_v15, v15_to_v16,
+];
+
+/// Brings `value` up to [`VERSION`]. Settings without a `version` are taken
+/// to be hand-written for the current schema.
+pub fn upgrade(mut value: Value) -> Result<Value, String> {
+    let settings = value
+        .as_object_mut()
+        .ok_or_else(|| "Settings must be a JSON object".to_owned())?;
+    let mut version = match settings.get("version") {
+        None => VERSION,
+        Some(v) => v
+            .as_i64()
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| format!("Invalid settings version: {}", v))? as i32,
+    };
+    if version > VERSION {
+        return Err(format!(
+            "Settings are from a newer version of genny ({} > {})",
+            version, VERSION
+        ));
+    }
+    while version < VERSION {
+        MIGRATIONS[version as usize - 1](settings);
+        version += 1;
+    }
+    settings.insert("version".to_owned(), version.into());
+    Ok(value)
+}
+
+/// v2 added Strings mode; everything before it drew squares.
+fn v1_to_v2(settings: &mut Map<String, Value>) {
+    settings
+        .entry("mode")
+        .or_insert_with(|| Value::String("Squares".to_owned()));
+}
+
+/// v3 made drawings reproducible with a seed. Older settings never pinned
+/// one, so they keep drawing something new each time.
+fn v2_to_v3(settings: &mut Map<String, Value>) {
+    settings.remove("seed");
+    settings.insert("seed_locked".to_owned(), Value::Bool(false));
+}
+
+/// v4 added user-made palettes.
+fn v3_to_v4(settings: &mut Map<String, Value>) {
+    settings.insert("custom_palettes".to_owned(), Value::Array(vec![]));
+}
+
+/// v5 added the palette generator. Its settings start out at their defaults,
+/// like a fresh install.
+fn v4_to_v5(_settings: &mut Map<String, Value>) {}
+
+/// v6 gave Squares mode settings of its own, so `"Squares"` became
+/// `{"Squares": {...}}` like Strings.
+fn v5_to_v6(settings: &mut Map<String, Value>) {
+    if let Some(mode) = settings.get_mut("mode") {
+        if mode == "Squares" {
+            *mode = serde_json::json!({ "Squares": {} });
+        }
+    }
+}
+
+/// v7 added other ways of coloring squares. Older settings keep coloring by
+/// cluster size, the default.
+fn v6_to_v7(_settings: &mut Map<String, Value>) {}
+
+/// v8 made link chances tunable. Older settings keep the 1 in 3 chance of
+/// the defaults.
+fn v7_to_v8(_settings: &mut Map<String, Value>) {}
+
+/// v9 added Maze mode, which older versions can't draw.
+fn v8_to_v9(_settings: &mut Map<String, Value>) {}
+
+/// v10 added hexagonal and triangular grids to Squares mode. Older settings
+/// keep drawing squares.
+fn v9_to_v10(_settings: &mut Map<String, Value>) {}
+
+/// v11 replaced `step` with the size of and gap between cells. Cells used to
+/// be 10 units wide whatever the step, so the rest of it is the gap.
+fn v10_to_v11(settings: &mut Map<String, Value>) {
+    if let Some(step) = settings.remove("step").and_then(|step| step.as_u64()) {
+        let size = step.min(10);
+        settings.insert(
+            "cells".to_owned(),
+            serde_json::json!({ "size": size, "gap": step - size }),
+        );
+    }
+}
+
+/// v12 added the Contour variant, with sharp corners hugging the clusters
+/// by default.
+fn v11_to_v12(_settings: &mut Map<String, Value>) {}
+
+/// v13 let Strings mode stretch between any two shapes. The circle's
+/// `radius`, in units whatever the canvas size, becomes the scale of the
+/// inner shape.
+fn v12_to_v13(settings: &mut Map<String, Value>) {
+    let width = match settings.get("size").and_then(|size| size.as_str()) {
+        Some("Medium") => 340.0,
+        Some("Large") => 680.0,
+        _ => 170.0,
+    };
+    let mode = match settings.get_mut("mode") {
+        Some(mode) => mode,
+        None => return,
+    };
+    if mode == "Strings" {
+        *mode = serde_json::json!({ "Strings": {} });
+    }
+    if let Some(props) = mode
+        .get_mut("Strings")
+        .and_then(|props| props.as_object_mut())
+    {
+        let radius = match props.remove("radius").as_ref().and_then(|r| r.as_str()) {
+            Some("Small") => 15.0,
+            Some("Medium") => 30.0,
+            _ => 45.0,
+        };
+        props.insert(
+            "inner".to_owned(),
+            serde_json::json!({ "scale": radius / (width / 2.0) }),
+        );
+    }
+}
+
+/// v14 let Strings mode draw any number of layers. The two strings it used
+/// to draw become the first two, with the `aperture` between them going to
+/// the second.
+fn v13_to_v14(settings: &mut Map<String, Value>) {
+    let props = settings
+        .get_mut("mode")
+        .and_then(|mode| mode.get_mut("Strings"))
+        .and_then(|props| props.as_object_mut());
+    if let Some(props) = props {
+        let aperture = props.remove("aperture").unwrap_or_else(|| 30.into());
+        props.insert(
+            "layers".to_owned(),
+            serde_json::json!([
+                {},
+                { "aperture": aperture, "direction": "Backward", "color": { "Palette": 1.0 } },
+            ]),
+        );
+    }
+}
+
+/// v15 added curved strings. Older settings keep them straight.
+fn v14_to_v15(_settings: &mut Map<String, Value>) {}
+
+/// v16 let Strings mode split its shapes into any number of points, rather
+/// than few, some or lots.
+fn v15_to_v16(settings: &mut Map<String, Value>) {
+    let props = settings
+        .get_mut("mode")
+        .and_then(|mode| mode.get_mut("Strings"))
+        .and_then(|props| props.as_object_mut());
+    if let Some(props) = props {
+        let splits = match props.get("splits").and_then(|splits| splits.as_str()) {
+            Some("Few") => 40,
+            Some("Some") => 80,
+            _ => 160,
+        };
+        props.insert("splits".to_owned(), splits.into());
+    }
+}
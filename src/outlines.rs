@@ -0,0 +1,432 @@
+//! The shapes of Strings mode as closed outlines, with points spread evenly
+//! along them.
+
+use crate::config::{ModelProperties, StringEnd, StringShape};
+use std::f32::consts::PI;
+
+/// Lines to round shapes.
+const ROUND_STEPS: usize = 720;
+
+/// Lines to each rounded corner, and to each curve of a path.
+const CURVE_STEPS: usize = 32;
+
+/// `n` points evenly spread along the outline of `end`, on the canvas.
+pub(crate) fn points(end: &StringEnd, n: usize, p: &ModelProperties) -> Vec<(f32, f32)> {
+    place(end, p, spread(&unit_outline(&end.shape), n))
+}
+
+/// The outline of `end` on the canvas.
+pub(crate) fn outline(end: &StringEnd, p: &ModelProperties) -> Vec<(f32, f32)> {
+    place(end, p, unit_outline(&end.shape))
+}
+
+/// Scales, rotates and moves points around the origin to where `end` goes.
+fn place(end: &StringEnd, p: &ModelProperties, points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    let (width, height) = (p.width() as f32, p.height() as f32);
+    let scale = end.scale * width / 2.0;
+    let (sin, cos) = end.rotation.to_radians().sin_cos();
+    let center = (end.center.0 * width, end.center.1 * height);
+    points
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = (x * scale, y * scale);
+            (center.0 + x * cos - y * sin, center.1 + x * sin + y * cos)
+        })
+        .collect()
+}
+
+/// The outline of `shape` around the origin, reaching 1 away at most and
+/// going clockwise. Empty for path data that can't be read.
+fn unit_outline(shape: &StringShape) -> Vec<(f32, f32)> {
+    // from the left, clockwise
+    let round = |point: &dyn Fn(f32, f32) -> (f32, f32)| {
+        (0..ROUND_STEPS)
+            .map(|k| {
+                let (sin, cos) = (2.0 * PI * k as f32 / ROUND_STEPS as f32).sin_cos();
+                point(-cos, -sin)
+            })
+            .collect()
+    };
+    // the longer side reaches 1
+    let sides = |ratio: f32| {
+        if ratio <= 1.0 {
+            (1.0, ratio)
+        } else {
+            (1.0 / ratio, 1.0)
+        }
+    };
+    let around = |corners: usize, radius: &dyn Fn(usize) -> f32| {
+        (0..corners)
+            .map(|k| {
+                let angle = (-90.0 + 360.0 * k as f32 / corners as f32).to_radians();
+                (radius(k) * angle.cos(), radius(k) * angle.sin())
+            })
+            .collect()
+    };
+    match shape {
+        StringShape::Circle => round(&|x, y| (x, y)),
+        StringShape::Ellipse { ratio } => {
+            let (w, h) = sides(*ratio);
+            round(&|x, y| (x * w, y * h))
+        }
+        StringShape::Polygon { sides } => around((*sides).max(3), &|_| 1.0),
+        StringShape::Star { points, inner } => around(2 * (*points).max(2), &|k| {
+            if k % 2 == 0 {
+                1.0
+            } else {
+                *inner
+            }
+        }),
+        StringShape::RoundedRect { ratio, radius } => {
+            let (w, h) = sides(*ratio);
+            let r = radius.clamp(0.0, 1.0) * w.min(h);
+            let steps = if r > 0.0 { CURVE_STEPS } else { 0 };
+            // corners clockwise from the top right, by center and first angle
+            let corners = [
+                ((w - r, -h + r), -90.0),
+                ((w - r, h - r), 0.0),
+                ((-w + r, h - r), 90.0),
+                ((-w + r, -h + r), 180.0),
+            ];
+            let mut points: Vec<(f32, f32)> = corners
+                .iter()
+                .flat_map(|&((cx, cy), from): &((f32, f32), f32)| {
+                    (0..=steps).map(move |k| {
+                        let angle = (from + 90.0 * k as f32 / steps.max(1) as f32).to_radians();
+                        (cx + r * angle.cos(), cy + r * angle.sin())
+                    })
+                })
+                .collect();
+            // start where the top side does
+            points.rotate_right(1);
+            points
+        }
+        StringShape::Superellipse { exponent } => {
+            let power = 2.0 / exponent.max(0.01);
+            let bend = |t: f32| t.signum() * t.abs().powf(power);
+            round(&|x, y| (bend(x), bend(y)))
+        }
+        StringShape::Path { d } => parse_path(d).map(fit).unwrap_or_default(),
+    }
+}
+
+/// Centers path points on the origin, scales them to reach 1 and turns
+/// them clockwise.
+fn fit(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+    for (x, y) in points.iter() {
+        min = (min.0.min(*x), min.1.min(*y));
+        max = (max.0.max(*x), max.1.max(*y));
+    }
+    let center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+    let extent = ((max.0 - min.0) / 2.0).max((max.1 - min.1) / 2.0);
+    if extent <= 0.0 {
+        return vec![];
+    }
+    for point in points.iter_mut() {
+        *point = ((point.0 - center.0) / extent, (point.1 - center.1) / extent);
+    }
+    // twice the signed area, positive for clockwise as y points down
+    let area: f32 = (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    if area < 0.0 {
+        points[1..].reverse();
+    }
+    points
+}
+
+/// `n` points along a closed outline, as far apart from each other as
+/// they can be, starting at its first point.
+fn spread(outline: &[(f32, f32)], n: usize) -> Vec<(f32, f32)> {
+    if outline.is_empty() {
+        return vec![];
+    }
+    let segment = |i: usize| (outline[i], outline[(i + 1) % outline.len()]);
+    let length = |(a, b): ((f32, f32), (f32, f32))| (b.0 - a.0).hypot(b.1 - a.1);
+    let total: f32 = (0..outline.len()).map(|i| length(segment(i))).sum();
+    let mut points = Vec::with_capacity(n);
+    // the segment being walked, and the length of the ones before it
+    let (mut i, mut walked) = (0, 0.0);
+    for k in 0..n {
+        let target = total * k as f32 / n as f32;
+        while i + 1 < outline.len() && walked + length(segment(i)) < target {
+            walked += length(segment(i));
+            i += 1;
+        }
+        let (a, b) = segment(i);
+        let t = if length((a, b)) > 0.0 {
+            ((target - walked) / length((a, b))).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        points.push((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+    }
+    points
+}
+
+/// The points of SVG path data, with curves and arcs flattened to lines.
+/// Every subpath is followed in turn.
+pub(crate) fn parse_path(d: &str) -> Result<Vec<(f32, f32)>, String> {
+    let tokens = tokenize(d)?;
+    let mut points: Vec<(f32, f32)> = vec![];
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    // the last control point, for the smooth curves that mirror it
+    let mut control: Option<(char, (f32, f32))> = None;
+    let mut command: Option<char> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Token::Command(c) = tokens[i] {
+            command = Some(c);
+            i += 1;
+            if c.eq_ignore_ascii_case(&'z') {
+                current = start;
+                control = None;
+                continue;
+            }
+        }
+        let c = command.ok_or_else(|| "Path data must start with a command".to_owned())?;
+        let arguments = match c.to_ascii_uppercase() {
+            'H' | 'V' => 1,
+            'M' | 'L' | 'T' => 2,
+            'S' | 'Q' => 4,
+            'C' => 6,
+            'A' => 7,
+            _ => {
+                return Err(format!(
+                    "Path data repeats a command without numbers: {}",
+                    c
+                ))
+            }
+        };
+        let numbers = tokens
+            .get(i..i + arguments)
+            .and_then(|args| {
+                args.iter()
+                    .map(|t| match t {
+                        Token::Number(n) => Some(*n),
+                        Token::Command(_) => None,
+                    })
+                    .collect::<Option<Vec<f32>>>()
+            })
+            .ok_or_else(|| format!("Path command {} needs {} numbers", c, arguments))?;
+        i += arguments;
+        let relative = c.is_ascii_lowercase();
+        let at = |x: f32, y: f32| {
+            if relative {
+                (current.0 + x, current.1 + y)
+            } else {
+                (x, y)
+            }
+        };
+        // the control point a smooth curve starts with: the last one
+        // mirrored, if the last command was a curve of the same kind
+        let mirrored = |kind: char| match control {
+            Some((k, (x, y))) if k == kind => (2.0 * current.0 - x, 2.0 * current.1 - y),
+            _ => current,
+        };
+        let (end, next_control) = match (c.to_ascii_uppercase(), &numbers[..]) {
+            ('M', &[x, y]) => {
+                start = at(x, y);
+                // more pairs are lines
+                command = Some(if relative { 'l' } else { 'L' });
+                points.push(start);
+                (start, None)
+            }
+            ('L', &[x, y]) => (line(&mut points, at(x, y)), None),
+            ('H', &[x]) => {
+                let x = if relative { current.0 + x } else { x };
+                (line(&mut points, (x, current.1)), None)
+            }
+            ('V', &[y]) => {
+                let y = if relative { current.1 + y } else { y };
+                (line(&mut points, (current.0, y)), None)
+            }
+            ('C', &[x1, y1, x2, y2, x, y]) => {
+                let (c1, c2, end) = (at(x1, y1), at(x2, y2), at(x, y));
+                points.extend(cubic(current, c1, c2, end));
+                (end, Some(('C', c2)))
+            }
+            ('S', &[x2, y2, x, y]) => {
+                let (c1, c2, end) = (mirrored('C'), at(x2, y2), at(x, y));
+                points.extend(cubic(current, c1, c2, end));
+                (end, Some(('C', c2)))
+            }
+            ('Q', &[x1, y1, x, y]) => {
+                let (c1, end) = (at(x1, y1), at(x, y));
+                points.extend(quadratic(current, c1, end));
+                (end, Some(('Q', c1)))
+            }
+            ('T', &[x, y]) => {
+                let (c1, end) = (mirrored('Q'), at(x, y));
+                points.extend(quadratic(current, c1, end));
+                (end, Some(('Q', c1)))
+            }
+            ('A', &[rx, ry, rotation, large, sweep, x, y]) => {
+                let end = at(x, y);
+                points.extend(arc(
+                    current,
+                    (rx, ry),
+                    rotation,
+                    large != 0.0,
+                    sweep != 0.0,
+                    end,
+                ));
+                (end, None)
+            }
+            _ => unreachable!("numbers are counted by command"),
+        };
+        current = end;
+        control = next_control;
+    }
+    // the path is closed anyway
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 2 {
+        return Err("Path data must draw something".to_owned());
+    }
+    Ok(points)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+fn tokenize(d: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = d.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if "MmLlHhVvCcSsQqTtAaZz".contains(c) {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else {
+            // a sign, digits with at most one dot, and an exponent; a second
+            // dot starts the next number, as in "0.5.5"
+            let from = i;
+            if chars[i] == '-' || chars[i] == '+' {
+                i += 1;
+            }
+            let mut dot = false;
+            while i < chars.len() && (chars[i].is_ascii_digit() || (chars[i] == '.' && !dot)) {
+                dot |= chars[i] == '.';
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '-' || chars[i] == '+') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let number: String = chars[from..i].iter().collect();
+            let number = number
+                .parse()
+                .map_err(|_| format!("Unexpected {:?} in path data", c))?;
+            tokens.push(Token::Number(number));
+        }
+    }
+    Ok(tokens)
+}
+
+fn line(points: &mut Vec<(f32, f32)>, to: (f32, f32)) -> (f32, f32) {
+    points.push(to);
+    to
+}
+
+/// The points of a quadratic Bézier curve after `from`.
+fn quadratic(from: (f32, f32), c: (f32, f32), to: (f32, f32)) -> Vec<(f32, f32)> {
+    (1..=CURVE_STEPS)
+        .map(|k| {
+            let t = k as f32 / CURVE_STEPS as f32;
+            let (a, b, d) = ((1.0 - t).powi(2), 2.0 * t * (1.0 - t), t * t);
+            (
+                a * from.0 + b * c.0 + d * to.0,
+                a * from.1 + b * c.1 + d * to.1,
+            )
+        })
+        .collect()
+}
+
+/// The points of a cubic Bézier curve after `from`.
+fn cubic(from: (f32, f32), c1: (f32, f32), c2: (f32, f32), to: (f32, f32)) -> Vec<(f32, f32)> {
+    (1..=CURVE_STEPS)
+        .map(|k| {
+            let t = k as f32 / CURVE_STEPS as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * t * u * u, 3.0 * t * t * u, t * t * t);
+            (
+                a * from.0 + b * c1.0 + c * c2.0 + d * to.0,
+                a * from.1 + b * c1.1 + c * c2.1 + d * to.1,
+            )
+        })
+        .collect()
+}
+
+/// The points of an elliptical arc after `from`, worked out from its end
+/// points as in the SVG specification (appendix F.6.5).
+fn arc(
+    from: (f32, f32),
+    (rx, ry): (f32, f32),
+    rotation: f32,
+    large: bool,
+    sweep: bool,
+    to: (f32, f32),
+) -> Vec<(f32, f32)> {
+    if from == to {
+        return vec![];
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        return vec![to];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.0 - to.0) / 2.0, (from.1 - to.1) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    // radii too small to reach are scaled up
+    let lambda = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+    let denominator = (rx * y1).powi(2) + (ry * x1).powi(2);
+    let sign = if large == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = (
+        cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0,
+        sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0,
+    );
+    let angle = |u: (f32, f32), v: (f32, f32)| (u.0 * v.1 - u.1 * v.0).atan2(u.0 * v.0 + u.1 * v.1);
+    let u = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let v = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let start = angle((1.0, 0.0), u);
+    let mut delta = angle(u, v);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+    (1..=CURVE_STEPS)
+        .map(|k| {
+            let (s, c) = (start + delta * k as f32 / CURVE_STEPS as f32).sin_cos();
+            (
+                center.0 + cos * rx * c - sin * ry * s,
+                center.1 + sin * rx * c + cos * ry * s,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,130 @@
+//! Builds harmonious palettes in LCH, where equal steps in lightness and hue
+//! look like equal steps to the eye.
+
+use super::Palette;
+use palette::convert::FromColorUnclamped;
+use palette::{Clamp, Lch, Pixel, Srgb};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// The name the generated palette goes by in the theme selector.
+pub const NAME: &str = "random";
+
+/// How the hues of a generated palette relate to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Harmony {
+    /// Neighbouring hues within 60°.
+    Analogous,
+    /// A hue and its opposite.
+    Complementary,
+    /// Three hues 120° apart.
+    Triadic,
+    /// A single hue.
+    Monochrome,
+}
+
+impl Harmony {
+    pub const ALL: [Harmony; 4] = [
+        Harmony::Analogous,
+        Harmony::Complementary,
+        Harmony::Triadic,
+        Harmony::Monochrome,
+    ];
+}
+
+impl fmt::Display for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Harmony::Analogous => write!(f, "Analogous"),
+            Harmony::Complementary => write!(f, "Complementary"),
+            Harmony::Triadic => write!(f, "Triadic"),
+            Harmony::Monochrome => write!(f, "Monochrome"),
+        }
+    }
+}
+
+impl FromStr for Harmony {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Harmony::ALL
+            .into_iter()
+            .find(|h| h.to_string() == s)
+            .ok_or_else(|| format!("Unknown harmony: {}", s))
+    }
+}
+
+/// Settings for the generated palette. The same settings always give the
+/// same colors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaletteGenerator {
+    pub harmony: Harmony,
+    pub count: usize,
+    /// LCH lightness range, 0 to 100. Colors ramp from dark to light.
+    pub lightness: (f32, f32),
+    /// LCH chroma range, 0 (gray) to about 130.
+    pub chroma: (f32, f32),
+    pub seed: u64,
+}
+
+impl Default for PaletteGenerator {
+    fn default() -> Self {
+        Self {
+            harmony: Harmony::Analogous,
+            count: 5,
+            lightness: (30.0, 85.0),
+            chroma: (30.0, 70.0),
+            seed: rand::thread_rng().gen(),
+        }
+    }
+}
+
+impl PaletteGenerator {
+    pub fn generate(&self) -> Palette {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let count = self.count.max(1);
+        let base_hue: f32 = rng.gen_range(0.0..360.0);
+        let colors = (0..count)
+            .map(|i| {
+                // position along the ramp, 0 to 1
+                let t = if count == 1 {
+                    0.5
+                } else {
+                    i as f32 / (count - 1) as f32
+                };
+                let hue = base_hue
+                    + match self.harmony {
+                        Harmony::Analogous => -30.0 + 60.0 * t,
+                        Harmony::Complementary => 180.0 * (i % 2) as f32,
+                        Harmony::Triadic => 120.0 * (i % 3) as f32,
+                        Harmony::Monochrome => 0.0,
+                    }
+                    + rng.gen_range(-5.0..5.0);
+                let lightness = lerp(self.lightness, t);
+                let chroma = lerp(self.chroma, rng.gen_range(0.0..1.0));
+                to_hex(Lch::new(lightness, chroma, hue.rem_euclid(360.0)))
+            })
+            .collect();
+        Palette {
+            name: NAME.to_owned(),
+            colors,
+        }
+    }
+}
+
+fn lerp((from, to): (f32, f32), t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// Converts to sRGB, giving up chroma rather than hue or lightness when the
+/// color is out of gamut.
+fn to_hex(mut lch: Lch) -> String {
+    let mut rgb = Srgb::from_color_unclamped(lch);
+    while !rgb.is_within_bounds() && lch.chroma > 1.0 {
+        lch.chroma *= 0.95;
+        rgb = Srgb::from_color_unclamped(lch);
+    }
+    let [r, g, b]: [u8; 3] = rgb.clamp().into_format().into_raw();
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
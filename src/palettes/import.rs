@@ -0,0 +1,268 @@
+//! Reads palettes from the interchange formats designers keep them in: GIMP
+//! `.gpl`, Adobe `.ase`, and plain lists of hex colors such as coolors.co
+//! exports and URLs.
+
+use super::Palette;
+use palette::chromatic_adaptation::AdaptInto;
+use palette::white_point::{D50, D65};
+use palette::{FromColor, Lab, Pixel, Srgb, Xyz};
+
+/// Parses `contents` as a palette. The format is picked by the file's
+/// extension, falling back to sniffing the contents. Palettes without a name
+/// of their own are named after the file.
+pub fn parse(file_name: &str, contents: &[u8]) -> Result<Palette, String> {
+    let stem = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(file_name)
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    let palette = if extension == "ase" || contents.starts_with(b"ASEF") {
+        parse_ase(contents)
+    } else {
+        let text = std::str::from_utf8(contents)
+            .map_err(|_| "Palette files other than .ase must be text".to_owned())?;
+        if extension == "gpl" || text.starts_with("GIMP Palette") {
+            parse_gpl(text)
+        } else {
+            parse_hex_list(text)
+        }
+    }
+    .map_err(|e| format!("{}: {}", file_name, e))?;
+    Ok(Palette {
+        name: palette
+            .name
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| stem.to_owned()),
+        colors: palette.colors,
+    })
+}
+
+/// A palette as found in a file, which may not name it.
+struct Parsed {
+    name: Option<String>,
+    colors: Vec<String>,
+}
+
+impl Parsed {
+    fn non_empty(self) -> Result<Parsed, String> {
+        if self.colors.is_empty() {
+            Err("no colors found".to_owned())
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// GIMP palettes: a `GIMP Palette` header, optional `Name:` and `Columns:`
+/// lines, `#` comments, and one `R G B [name]` line per color.
+fn parse_gpl(text: &str) -> Result<Parsed, String> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Palette" => {}
+        _ => return Err("missing \"GIMP Palette\" header".to_owned()),
+    }
+    let mut parsed = Parsed {
+        name: None,
+        colors: vec![],
+    };
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("Name:") {
+            parsed.name = Some(name.trim().to_owned());
+            continue;
+        }
+        let channels = line
+            .split_whitespace()
+            .take(3)
+            .map(|c| c.parse::<u8>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|c| c.len() == 3)
+            .ok_or_else(|| format!("line {}: expected \"R G B\", found {:?}", i + 1, line))?;
+        parsed
+            .colors
+            .push(hex(channels[0], channels[1], channels[2]));
+    }
+    parsed.non_empty()
+}
+
+/// Adobe Swatch Exchange: a big-endian `ASEF` file of blocks, of which
+/// color entries (`0x0001`) and group starts (`0xC001`) matter here.
+fn parse_ase(bytes: &[u8]) -> Result<Parsed, String> {
+    let mut r = Reader { bytes, at: 0 };
+    if r.take(4)? != b"ASEF" {
+        return Err("not an Adobe Swatch Exchange file".to_owned());
+    }
+    r.take(4)?; // version
+    let blocks = r.u32()?;
+    let mut parsed = Parsed {
+        name: None,
+        colors: vec![],
+    };
+    for _ in 0..blocks {
+        let kind = r.u16()?;
+        let length = r.u32()? as usize;
+        let mut block = Reader {
+            bytes: r.take(length)?,
+            at: 0,
+        };
+        match kind {
+            0xC001 => {
+                let name = block.utf16()?;
+                parsed.name.get_or_insert(name);
+            }
+            0x0001 => {
+                let name = block.utf16()?;
+                let model = block.take(4)?;
+                let color = match model {
+                    b"RGB " => {
+                        let (r, g, b) = (block.f32()?, block.f32()?, block.f32()?);
+                        hex(unit(r), unit(g), unit(b))
+                    }
+                    b"Gray" => {
+                        let v = unit(block.f32()?);
+                        hex(v, v, v)
+                    }
+                    b"CMYK" => {
+                        let (c, m, y, k) = (block.f32()?, block.f32()?, block.f32()?, block.f32()?);
+                        let channel = |v: f32| unit((1.0 - v) * (1.0 - k));
+                        hex(channel(c), channel(m), channel(y))
+                    }
+                    b"LAB " => {
+                        let lab = Lab::<D50, f32>::with_wp(
+                            block.f32()? * 100.0,
+                            block.f32()?,
+                            block.f32()?,
+                        );
+                        // ASE stores Lab relative to D50; sRGB is D65
+                        let xyz: Xyz<D65, f32> = Xyz::<D50, f32>::from_color(lab).adapt_into();
+                        let [r, g, b]: [u8; 3] =
+                            Srgb::from_color(xyz).into_format::<u8>().into_raw();
+                        hex(r, g, b)
+                    }
+                    _ => {
+                        return Err(format!(
+                            "color {:?} uses the unsupported {:?} color model",
+                            name,
+                            String::from_utf8_lossy(model)
+                        ))
+                    }
+                };
+                parsed.colors.push(color);
+            }
+            _ => {}
+        }
+    }
+    parsed.non_empty()
+}
+
+/// Lists of hex colors in any of the shapes they get passed around in:
+/// one per line, comma separated, CSS variables or a coolors.co URL like
+/// `https://coolors.co/264653-2a9d8f-e9c46a`. Colors are written with a `#`,
+/// or else fill a line (or the end of a URL) on their own, so that words
+/// like "facade" in free text aren't taken for colors.
+fn parse_hex_list(text: &str) -> Result<Parsed, String> {
+    let needs_hash = text.contains('#');
+    let mut colors = vec![];
+    for line in text.lines() {
+        if needs_hash {
+            colors.extend(
+                tokens(line)
+                    .filter_map(|token| token.strip_prefix('#'))
+                    .filter_map(|digits| hex_color(digits, true)),
+            );
+        } else {
+            let line = line.trim();
+            let values = if line.starts_with("http://") || line.starts_with("https://") {
+                line.rsplit('/').next().unwrap_or(line)
+            } else {
+                line
+            };
+            let found: Option<Vec<String>> = tokens(values)
+                .map(|digits| hex_color(digits, false))
+                .collect();
+            colors.extend(found.unwrap_or_default());
+        }
+    }
+    Parsed { name: None, colors }
+        .non_empty()
+        .map_err(|_| "no hex colors like #264653 found".to_owned())
+}
+
+/// The words of `line`, keeping the `#` of colors.
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+        .filter(|token| !token.is_empty())
+}
+
+/// `#RRGGBB` from the digits of a hex color, with short `rgb` ones allowed
+/// if `short`.
+fn hex_color(digits: &str, short: bool) -> Option<String> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match digits.len() {
+        // #rrggbbaa: alpha is dropped
+        6 | 8 => Some(format!("#{}", &digits[..6]).to_uppercase()),
+        3 if short => {
+            let d: Vec<char> = digits.chars().collect();
+            Some(format!("#{0}{0}{1}{1}{2}{2}", d[0], d[1], d[2]).to_uppercase())
+        }
+        _ => None,
+    }
+}
+
+fn unit(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn hex(r: u8, g: u8, b: u8) -> String {
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// Big-endian cursor over an `.ase` file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.at.checked_add(n);
+        let bytes = end
+            .and_then(|end| self.bytes.get(self.at..end))
+            .ok_or_else(|| "file ends unexpectedly".to_owned())?;
+        self.at += n;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// A length-prefixed, NUL-terminated UTF-16 string.
+    fn utf16(&mut self) -> Result<String, String> {
+        let length = self.u16()? as usize;
+        let units = (0..length)
+            .map(|_| self.u16())
+            .collect::<Result<Vec<_>, _>>()?;
+        let units = units.strip_suffix(&[0]).unwrap_or(&units);
+        String::from_utf16(units).map_err(|_| "invalid name".to_owned())
+    }
+}
@@ -0,0 +1,77 @@
+//! Named color palettes: the ones shipped with genny and the ones users make.
+
+use serde::{Deserialize, Serialize};
+
+pub mod generate;
+pub mod import;
+pub mod vision;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    /// `#rrggbb` colors, at least one.
+    pub colors: Vec<String>,
+}
+
+impl Palette {
+    pub fn new(name: &str, colors: &[&str]) -> Palette {
+        Palette {
+            name: name.to_owned(),
+            colors: colors.iter().map(|c| (*c).to_owned()).collect(),
+        }
+    }
+}
+
+/// The palettes that ship with genny, in the order the theme selector lists
+/// them.
+pub fn builtin() -> Vec<Palette> {
+    vec![
+        Palette::new(
+            "accented",
+            &["#011627", "#D5CAD6", "#2EC4B6", "#E71D36", "#FF9F1C"],
+        ),
+        Palette::new(
+            "bluish",
+            &["#CDC392", "#E8E5DA", "#9EB7E5", "#648DE5", "#304C89"],
+        ),
+        Palette::new(
+            "pastel",
+            &["#1A535C", "#4ECDC4", "#721817", "#FF6B6B", "#F49D37"],
+        ),
+        Palette::new(
+            "reddish",
+            &["#370617", "#DC2F02", "#F48C06", "#FFBA08", "#9D0208"],
+        ),
+        Palette::new(
+            "tropical",
+            &["#BF3100", "#8EA604", "#D76A03", "#EC9F05", "#F5BB00"],
+        ),
+    ]
+}
+
+/// `name`, or `name 2`, `name 3`... if a palette in `taken` already has it.
+pub fn unique_name(name: &str, taken: &[Palette]) -> String {
+    let free = |candidate: &str| taken.iter().all(|p| p.name != candidate);
+    if free(name) {
+        return name.to_owned();
+    }
+    (2..)
+        .map(|i| format!("{} {}", name, i))
+        .find(|candidate| free(candidate))
+        .expect("some suffix is always free")
+}
+
+/// Whether `name` belongs to a palette genny provides, built-in or
+/// generated, which users can neither edit nor reuse the name of.
+pub fn is_reserved(name: &str) -> bool {
+    name == generate::NAME || builtin().iter().any(|p| p.name == name)
+}
+
+/// Checks that `color` is a `#rrggbb` color, the form every renderer and
+/// the HTML color picker understand.
+pub fn validate_color(color: &str) -> Result<(), String> {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+        _ => Err(format!("Invalid color, expected #rrggbb: {}", color)),
+    }
+}
@@ -0,0 +1,147 @@
+//! How palettes look to viewers with color-vision deficiencies, and whether
+//! they can still tell neighbouring colors apart.
+
+use crate::scene::rgb;
+use palette::{ColorDifference, FromColor, Lab, LinSrgb, Pixel, Srgb};
+use std::{fmt, str::FromStr};
+
+/// Colors closer than this CIEDE2000 distance are hard to tell apart at a
+/// glance.
+pub const MIN_DISTANCE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deficiency {
+    /// No red cones.
+    Protanopia,
+    /// No green cones.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 3] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+    ];
+
+    /// Machado, Oliveira and Fernandes (2009) at full severity, applied to
+    /// linear RGB.
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Deficiency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Deficiency::Protanopia => write!(f, "Protanopia"),
+            Deficiency::Deuteranopia => write!(f, "Deuteranopia"),
+            Deficiency::Tritanopia => write!(f, "Tritanopia"),
+        }
+    }
+}
+
+impl FromStr for Deficiency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Deficiency::ALL
+            .into_iter()
+            .find(|d| d.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown color vision deficiency: {}", s))
+    }
+}
+
+/// `color` as seen with `deficiency`. Colors that aren't RGB (`none`,
+/// `transparent`) are returned unchanged.
+pub fn simulate(color: &str, deficiency: Deficiency) -> String {
+    let [r, g, b] = match rgb(color) {
+        Some(rgb) => rgb,
+        None => return color.to_owned(),
+    };
+    let linear: LinSrgb = Srgb::new(r, g, b).into_format::<f32>().into_linear();
+    let m = deficiency.matrix();
+    let channel = |row: [f32; 3]| {
+        (row[0] * linear.red + row[1] * linear.green + row[2] * linear.blue).clamp(0.0, 1.0)
+    };
+    let seen = LinSrgb::new(channel(m[0]), channel(m[1]), channel(m[2]));
+    let [r, g, b]: [u8; 3] = Srgb::from_linear(seen).into_format().into_raw();
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+/// Two palette colors that are too close to each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    /// Index of the first color; the other one is the next.
+    pub index: usize,
+    /// `None` for typical color vision.
+    pub vision: Option<Deficiency>,
+    /// CIEDE2000 distance between the two.
+    pub distance: f32,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "colors {} and {} are hard to tell apart",
+            self.index + 1,
+            self.index + 2
+        )?;
+        if let Some(vision) = self.vision {
+            write!(f, " with {}", vision.to_string().to_lowercase())?;
+        }
+        write!(f, " (ΔE {:.1})", self.distance)
+    }
+}
+
+/// Checks that neighbouring colors, which Squares mode uses for neighbouring
+/// cluster sizes, stay distinguishable with typical vision and with each
+/// deficiency.
+pub fn analyze(colors: &[String]) -> Vec<Warning> {
+    let visions = std::iter::once(None).chain(Deficiency::ALL.into_iter().map(Some));
+    visions
+        .flat_map(|vision| {
+            let seen: Vec<Option<Lab>> = colors
+                .iter()
+                .map(|c| {
+                    let c = match vision {
+                        Some(deficiency) => simulate(c, deficiency),
+                        None => c.clone(),
+                    };
+                    rgb(&c).map(|[r, g, b]| Lab::from_color(Srgb::new(r, g, b).into_format()))
+                })
+                .collect();
+            seen.windows(2)
+                .enumerate()
+                .filter_map(|(index, pair)| match pair {
+                    [Some(a), Some(b)] => Some(Warning {
+                        index,
+                        vision,
+                        distance: a.get_color_difference(b),
+                    }),
+                    _ => None,
+                })
+                .filter(|w| w.distance < MIN_DISTANCE)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
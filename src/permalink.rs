@@ -0,0 +1,36 @@
+//! Keeps the page URL in sync with the settings, so that the address bar
+//! always holds a link to the drawing on screen.
+
+use crate::config::ModelProperties;
+use gloo::events::EventListener;
+use wasm_bindgen::JsValue;
+
+/// The properties encoded in the current URL, if it has any.
+pub fn read() -> Option<ModelProperties> {
+    let hash = gloo::utils::window().location().hash().ok()?;
+    let link = hash.strip_prefix('#').filter(|h| !h.is_empty())?;
+    match ModelProperties::from_permalink(link) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            log!("{}", e);
+            None
+        }
+    }
+}
+
+/// Points the URL at `p`, without adding a history entry.
+pub fn write(p: &ModelProperties) {
+    let url = format!("#{}", p.to_permalink());
+    gloo::utils::history()
+        .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+        .expect("failed to update the URL");
+}
+
+/// Calls `callback` whenever the user navigates to another permalink.
+pub fn on_change(callback: impl Fn(ModelProperties) + 'static) -> EventListener {
+    EventListener::new(&gloo::utils::window(), "hashchange", move |_| {
+        if let Some(p) = read() {
+            callback(p);
+        }
+    })
+}
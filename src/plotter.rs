@@ -0,0 +1,480 @@
+//! Pen-plotter output: the stroked geometry of a scene, one layer per color,
+//! with duplicate segments removed, contiguous segments joined into
+//! polylines, and polylines ordered to keep pen-up travel short.
+
+use crate::scene::{rgb, PathCmd, Point, Scene, Shape, Style};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// Points closer than this (in scene units) are considered the same.
+const EPSILON: f32 = 0.01;
+
+/// Everything one pen draws.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub color: String,
+    pub stroke_width: f32,
+    pub polylines: Vec<Vec<Point>>,
+}
+
+/// Maps local coordinates to scene coordinates.
+#[derive(Clone, Copy)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Affine {
+    const IDENTITY: Affine = Affine {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    fn apply(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    /// `self` applied after `other`.
+    fn then(&self, other: Affine) -> Affine {
+        Affine {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    fn translate(x: f32, y: f32) -> Affine {
+        Affine {
+            e: x,
+            f: y,
+            ..Affine::IDENTITY
+        }
+    }
+
+    fn rotate_at(degrees: f32, center: Point) -> Affine {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Affine::translate(center.x, center.y)
+            .then(Affine {
+                a: cos,
+                b: sin,
+                c: -sin,
+                d: cos,
+                e: 0.0,
+                f: 0.0,
+            })
+            .then(Affine::translate(-center.x, -center.y))
+    }
+}
+
+struct Segment {
+    color: String,
+    stroke_width: f32,
+    from: Point,
+    to: Point,
+}
+
+/// Collects the stroked geometry of `scene` into optimized per-pen layers, in
+/// the order the pens first appear. See [`Style::pen`].
+pub fn layers(scene: &Scene) -> Vec<Layer> {
+    let mut segments = vec![];
+    for shape in scene.shapes.iter() {
+        collect(
+            scene,
+            shape,
+            &Style::default(),
+            Affine::IDENTITY,
+            &mut segments,
+        );
+    }
+
+    let mut colors: Vec<(String, f32)> = vec![];
+    for segment in segments.iter() {
+        if !colors.iter().any(|(c, _)| *c == segment.color) {
+            colors.push((segment.color.clone(), segment.stroke_width));
+        }
+    }
+    colors
+        .into_iter()
+        .map(|(color, stroke_width)| {
+            let lines: Vec<(Point, Point)> = segments
+                .iter()
+                .filter(|s| s.color == color)
+                .map(|s| (s.from, s.to))
+                .collect();
+            Layer {
+                color,
+                stroke_width,
+                polylines: order(join(merge_overlaps(lines))),
+            }
+        })
+        .collect()
+}
+
+/// An SVG with one Inkscape layer per color, as expected by AxiDraw-style
+/// plotter software.
+pub fn to_svg(scene: &Scene) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    writeln!(
+        out,
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" "#,
+            r#"xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" "#,
+            r#"version="1.1" width="{w}" height="{h}" viewBox="0 0 {w} {h}" fill="none">"#
+        ),
+        w = scene.width,
+        h = scene.height
+    )
+    .unwrap();
+    for (i, layer) in layers(scene).iter().enumerate() {
+        writeln!(
+            out,
+            r#"<g inkscape:groupmode="layer" id="layer{n}" inkscape:label="{n} {color}" stroke="{color}" stroke-width="{width}" stroke-linecap="round" stroke-linejoin="round">"#,
+            n = i + 1,
+            color = layer.color,
+            width = layer.stroke_width
+        )
+        .unwrap();
+        for polyline in layer.polylines.iter() {
+            let d = polyline
+                .iter()
+                .enumerate()
+                .map(|(i, p)| format!("{} {} {}", if i == 0 { "M" } else { "L" }, p.x, p.y))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, r#"<path d="{}"/>"#, d).unwrap();
+        }
+        out.push_str("</g>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn collect(
+    scene: &Scene,
+    shape: &Shape,
+    parent: &Style,
+    transform: Affine,
+    out: &mut Vec<Segment>,
+) {
+    let inherit = |style: &Style| Style {
+        fill: style.fill.clone().or_else(|| parent.fill.clone()),
+        stroke: style.stroke.clone().or_else(|| parent.stroke.clone()),
+        stroke_width: style.stroke_width.or(parent.stroke_width),
+        round_caps: style.round_caps || parent.round_caps,
+        pen: style.pen.clone().or_else(|| parent.pen.clone()),
+        // pens can't fade or blend
+        ..Default::default()
+    };
+    let mut push = |style: &Style, points: Vec<Point>, closed: bool| {
+        let style = inherit(style);
+        let color = match (style.pen, style.stroke) {
+            (_, None) => return,
+            (_, Some(stroke)) if rgb(&stroke).is_none() => return,
+            (Some(pen), _) => pen,
+            (None, Some(stroke)) => stroke,
+        };
+        let mut points: Vec<Point> = points.into_iter().map(|p| transform.apply(p)).collect();
+        if closed {
+            if let Some(first) = points.first().copied() {
+                points.push(first);
+            }
+        }
+        for pair in points.windows(2) {
+            out.push(Segment {
+                color: color.clone(),
+                stroke_width: style.stroke_width.unwrap_or(1.0),
+                from: pair[0],
+                to: pair[1],
+            });
+        }
+    };
+    match shape {
+        Shape::Circle { center, r, style } => {
+            push(style, arc_points(*center, *r, 0.0, 360.0), true);
+        }
+        Shape::Rect {
+            at,
+            width,
+            height,
+            rx,
+            style,
+        } => push(style, rect_points(*at, *width, *height, *rx), true),
+        Shape::Path { cmds, style } => {
+            let mut current: Vec<Point> = vec![];
+            for cmd in cmds.iter() {
+                match cmd {
+                    PathCmd::MoveTo(p) => {
+                        push(style, std::mem::take(&mut current), false);
+                        current.push(*p);
+                    }
+                    PathCmd::LineTo(p) => current.push(*p),
+                    PathCmd::QuadTo(c, p) => {
+                        let from = current.last().copied().unwrap_or(*p);
+                        current.extend(bezier_points(&[from, *c, *p]));
+                    }
+                    PathCmd::CubicTo(c1, c2, p) => {
+                        let from = current.last().copied().unwrap_or(*p);
+                        current.extend(bezier_points(&[from, *c1, *c2, *p]));
+                    }
+                    PathCmd::Close => {
+                        let start = current.first().copied();
+                        push(style, std::mem::take(&mut current), true);
+                        current.extend(start);
+                    }
+                }
+            }
+            push(style, current, false);
+        }
+        Shape::Use { symbol, at, style } => {
+            if let Some(symbol) = scene.symbol(symbol) {
+                let style = inherit(style);
+                let transform = transform.then(Affine::translate(at.x, at.y));
+                for shape in symbol.shapes.iter() {
+                    collect(scene, shape, &style, transform, out);
+                }
+            }
+        }
+        Shape::Rotated {
+            degrees,
+            center,
+            shapes,
+        } => {
+            let transform = transform.then(Affine::rotate_at(*degrees, *center));
+            for shape in shapes.iter() {
+                collect(scene, shape, parent, transform, out);
+            }
+        }
+    }
+}
+
+/// Points along the Bézier curve with these control points, after the
+/// first one.
+fn bezier_points(controls: &[Point]) -> Vec<Point> {
+    // roughly one point per scene unit along the control polygon, which is
+    // never shorter than the curve
+    let length: f32 = controls.windows(2).map(|w| w[0].distance_to(&w[1])).sum();
+    let steps = (length.ceil() as usize).max(4);
+    (1..=steps)
+        .map(|step| {
+            let t = step as f32 / steps as f32;
+            // de Casteljau: interpolate between neighbours until one is left
+            let mut points = controls.to_vec();
+            while points.len() > 1 {
+                points = points
+                    .windows(2)
+                    .map(|w| {
+                        Point::new(
+                            w[0].x + (w[1].x - w[0].x) * t,
+                            w[0].y + (w[1].y - w[0].y) * t,
+                        )
+                    })
+                    .collect();
+            }
+            points[0]
+        })
+        .collect()
+}
+
+fn arc_points(center: Point, r: f32, from_degrees: f32, to_degrees: f32) -> Vec<Point> {
+    // roughly one point per scene unit of arc, but never fewer than a few
+    let steps = ((r * (to_degrees - from_degrees).to_radians()).abs().ceil() as usize).max(4);
+    (0..=steps)
+        .map(|i| {
+            let angle =
+                (from_degrees + (to_degrees - from_degrees) * i as f32 / steps as f32).to_radians();
+            Point::new(center.x + r * angle.cos(), center.y + r * angle.sin())
+        })
+        .collect()
+}
+
+fn rect_points(at: Point, width: f32, height: f32, rx: f32) -> Vec<Point> {
+    let rx = rx.min(width / 2.0).min(height / 2.0);
+    let (x0, y0, x1, y1) = (at.x, at.y, at.x + width, at.y + height);
+    if rx <= 0.0 {
+        return vec![
+            at,
+            Point::new(x1, y0),
+            Point::new(x1, y1),
+            Point::new(x0, y1),
+        ];
+    }
+    let mut points = vec![];
+    points.extend(arc_points(Point::new(x1 - rx, y0 + rx), rx, 270.0, 360.0));
+    points.extend(arc_points(Point::new(x1 - rx, y1 - rx), rx, 0.0, 90.0));
+    points.extend(arc_points(Point::new(x0 + rx, y1 - rx), rx, 90.0, 180.0));
+    points.extend(arc_points(Point::new(x0 + rx, y0 + rx), rx, 180.0, 270.0));
+    points
+}
+
+fn key(p: Point) -> (i64, i64) {
+    (
+        (p.x / EPSILON).round() as i64,
+        (p.y / EPSILON).round() as i64,
+    )
+}
+
+/// Drops zero-length segments and fuses collinear segments that overlap or
+/// touch, so shared edges are only drawn once. Segments stay in the order
+/// they're first drawn in.
+fn merge_overlaps(lines: Vec<(Point, Point)>) -> Vec<(Point, Point)> {
+    // segments on the same infinite line (keyed by direction and offset), as
+    // intervals along that direction; the original endpoints are kept so
+    // that joining them up later finds exact matches
+    type LineKey = (i64, i64, i64);
+    type Interval = ((f32, Point), (f32, Point), usize);
+    let mut by_line: BTreeMap<LineKey, Vec<Interval>> = BTreeMap::new();
+    for (i, (from, to)) in lines.into_iter().enumerate() {
+        let length = from.distance_to(&to);
+        if length < EPSILON {
+            continue;
+        }
+        let mut dir = Point::new((to.x - from.x) / length, (to.y - from.y) / length);
+        if dir.x < -1e-6 || (dir.x.abs() <= 1e-6 && dir.y < 0.0) {
+            dir = Point::new(-dir.x, -dir.y);
+        }
+        // signed distance of the line from the origin, along its normal
+        let offset = from.x * -dir.y + from.y * dir.x;
+        let line = (
+            (dir.x * 1e4).round() as i64,
+            (dir.y * 1e4).round() as i64,
+            (offset / EPSILON).round() as i64,
+        );
+        let along = |p: Point| (p.x * dir.x + p.y * dir.y, p);
+        let (a, b) = (along(from), along(to));
+        by_line
+            .entry(line)
+            .or_default()
+            .push(if a.0 <= b.0 { (a, b, i) } else { (b, a, i) });
+    }
+
+    let mut res = vec![];
+    for (_, mut intervals) in by_line {
+        intervals.sort_by(|a, b| a.0 .0.partial_cmp(&b.0 .0).unwrap());
+        let mut current = intervals[0];
+        for interval in intervals.into_iter().skip(1) {
+            if interval.0 .0 <= current.1 .0 + EPSILON {
+                if interval.1 .0 > current.1 .0 {
+                    current.1 = interval.1;
+                }
+                current.2 = current.2.min(interval.2);
+            } else {
+                res.push(current);
+                current = interval;
+            }
+        }
+        res.push(current);
+    }
+    res.sort_by_key(|interval| interval.2);
+    res.into_iter()
+        .map(|(from, to, _)| (from.1, to.1))
+        .collect()
+}
+
+/// Chains segments that share endpoints into polylines.
+fn join(lines: Vec<(Point, Point)>) -> Vec<Vec<Point>> {
+    let mut at_point: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, (from, to)) in lines.iter().enumerate() {
+        at_point.entry(key(*from)).or_default().push(i);
+        at_point.entry(key(*to)).or_default().push(i);
+    }
+    let mut used = vec![false; lines.len()];
+    let next_from = |p: Point, used: &mut Vec<bool>| -> Option<Point> {
+        let candidates = at_point.get(&key(p))?;
+        let i = *candidates.iter().find(|i| !used[**i])?;
+        used[i] = true;
+        let (from, to) = lines[i];
+        Some(if key(from) == key(p) { to } else { from })
+    };
+
+    // chains can only end where an odd number of segments meet, so starting
+    // from those points first gives fewer, longer polylines
+    let odd = |p: Point| at_point[&key(p)].len() % 2 == 1;
+    let mut starts: Vec<usize> = (0..lines.len()).collect();
+    starts.sort_by_key(|i| {
+        let (from, to) = lines[*i];
+        !(odd(from) || odd(to))
+    });
+
+    let mut res = vec![];
+    for i in starts {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let (from, to) = lines[i];
+        let mut polyline = vec![from, to];
+        while let Some(p) = next_from(*polyline.last().unwrap(), &mut used) {
+            polyline.push(p);
+        }
+        let mut head = vec![];
+        while let Some(p) = next_from(*head.last().unwrap_or(&from), &mut used) {
+            head.push(p);
+        }
+        head.reverse();
+        head.extend(polyline);
+        res.push(head);
+    }
+    res
+}
+
+/// Greedy nearest-neighbor ordering, reversing polylines when their end is
+/// closer than their start. Greedy choices can backfire, so the given order
+/// is kept when it travels less.
+fn order(polylines: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    let nearest = nearest_first(polylines.clone());
+    if travel(&nearest) <= travel(&polylines) {
+        nearest
+    } else {
+        polylines
+    }
+}
+
+/// How far the pen moves up, from the origin through `polylines` in order.
+fn travel(polylines: &[Vec<Point>]) -> f32 {
+    let mut pen = Point::new(0.0, 0.0);
+    let mut distance = 0.0;
+    for polyline in polylines {
+        distance += pen.distance_to(polyline.first().unwrap());
+        pen = *polyline.last().unwrap();
+    }
+    distance
+}
+
+fn nearest_first(mut polylines: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    let mut res = Vec::with_capacity(polylines.len());
+    let mut pen = Point::new(0.0, 0.0);
+    while !polylines.is_empty() {
+        let (i, reverse, _) = polylines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, polyline)| {
+                let start = pen.distance_to(polyline.first().unwrap());
+                let end = pen.distance_to(polyline.last().unwrap());
+                [(i, false, start), (i, true, end)]
+            })
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .unwrap();
+        let mut polyline = polylines.swap_remove(i);
+        if reverse {
+            polyline.reverse();
+        }
+        pen = *polyline.last().unwrap();
+        res.push(polyline);
+    }
+    res
+}
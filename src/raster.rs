@@ -0,0 +1,197 @@
+//! Rasterizes a [`Scene`] into a PNG at any pixel size.
+
+use crate::config::BlendMode;
+use crate::scene::{rgb, PathCmd, Point, Scene, Shape, Style};
+use tiny_skia::{Color, FillRule, LineCap, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+/// Presentation attributes after inheritance, as SVG would resolve them.
+#[derive(Clone)]
+struct Resolved {
+    fill: Option<String>,
+    stroke: Option<String>,
+    stroke_width: f32,
+    round_caps: bool,
+    opacity: f32,
+    blend: BlendMode,
+}
+
+impl Resolved {
+    fn inherit(&self, style: &Style) -> Resolved {
+        Resolved {
+            fill: style.fill.clone().or_else(|| self.fill.clone()),
+            stroke: style.stroke.clone().or_else(|| self.stroke.clone()),
+            stroke_width: style.stroke_width.unwrap_or(self.stroke_width),
+            round_caps: style.round_caps || self.round_caps,
+            opacity: self.opacity * style.opacity.unwrap_or(1.0),
+            blend: style.blend.unwrap_or(self.blend),
+        }
+    }
+}
+
+/// Renders `scene` into a `width`×`height` PNG. The drawing is scaled to fit
+/// and centered, like an SVG viewBox. With `transparent`, the scene's
+/// background is left out.
+pub fn to_png(
+    scene: &Scene,
+    width: u32,
+    height: u32,
+    transparent: bool,
+) -> Result<Vec<u8>, String> {
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| format!("Invalid PNG size: {}x{}", width, height))?;
+    if let (false, Some(color)) = (transparent, scene.background.as_deref().and_then(color)) {
+        pixmap.fill(color);
+    }
+
+    let scale = (width as f32 / scene.width).min(height as f32 / scene.height);
+    let transform = Transform::from_translate(
+        (width as f32 - scene.width * scale) / 2.0,
+        (height as f32 - scene.height * scale) / 2.0,
+    )
+    .pre_scale(scale, scale);
+    // the root <svg> sets fill="none"
+    let root = Resolved {
+        fill: None,
+        stroke: None,
+        stroke_width: 1.0,
+        round_caps: false,
+        opacity: 1.0,
+        blend: BlendMode::Normal,
+    };
+    for shape in scene.shapes.iter() {
+        draw_shape(&mut pixmap, scene, shape, &root, transform);
+    }
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Could not encode PNG: {}", e))
+}
+
+fn draw_shape(
+    pixmap: &mut Pixmap,
+    scene: &Scene,
+    shape: &Shape,
+    parent: &Resolved,
+    transform: Transform,
+) {
+    match shape {
+        Shape::Circle { center, r, style } => {
+            if let Some(path) = PathBuilder::from_circle(center.x, center.y, *r) {
+                paint_path(pixmap, &path, &parent.inherit(style), transform);
+            }
+        }
+        Shape::Rect {
+            at,
+            width,
+            height,
+            rx,
+            style,
+        } => {
+            if let Some(path) = rounded_rect(*at, *width, *height, *rx) {
+                paint_path(pixmap, &path, &parent.inherit(style), transform);
+            }
+        }
+        Shape::Path { cmds, style } => {
+            let mut pb = PathBuilder::new();
+            for cmd in cmds.iter() {
+                match cmd {
+                    PathCmd::MoveTo(p) => pb.move_to(p.x, p.y),
+                    PathCmd::LineTo(p) => pb.line_to(p.x, p.y),
+                    PathCmd::QuadTo(c, p) => pb.quad_to(c.x, c.y, p.x, p.y),
+                    PathCmd::CubicTo(c1, c2, p) => pb.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y),
+                    PathCmd::Close => pb.close(),
+                }
+            }
+            if let Some(path) = pb.finish() {
+                paint_path(pixmap, &path, &parent.inherit(style), transform);
+            }
+        }
+        Shape::Use { symbol, at, style } => {
+            if let Some(symbol) = scene.symbol(symbol) {
+                let resolved = parent.inherit(style);
+                let transform = transform.pre_translate(at.x, at.y);
+                for shape in symbol.shapes.iter() {
+                    draw_shape(pixmap, scene, shape, &resolved, transform);
+                }
+            }
+        }
+        Shape::Rotated {
+            degrees,
+            center,
+            shapes,
+        } => {
+            let transform =
+                transform.pre_concat(Transform::from_rotate_at(*degrees, center.x, center.y));
+            for shape in shapes.iter() {
+                draw_shape(pixmap, scene, shape, parent, transform);
+            }
+        }
+    }
+}
+
+fn paint_path(pixmap: &mut Pixmap, path: &tiny_skia::Path, style: &Resolved, transform: Transform) {
+    let mut paint = Paint {
+        blend_mode: match style.blend {
+            BlendMode::Normal => tiny_skia::BlendMode::SourceOver,
+            BlendMode::Multiply => tiny_skia::BlendMode::Multiply,
+            BlendMode::Screen => tiny_skia::BlendMode::Screen,
+            BlendMode::Overlay => tiny_skia::BlendMode::Overlay,
+            BlendMode::Darken => tiny_skia::BlendMode::Darken,
+            BlendMode::Lighten => tiny_skia::BlendMode::Lighten,
+            BlendMode::Difference => tiny_skia::BlendMode::Difference,
+            BlendMode::Exclusion => tiny_skia::BlendMode::Exclusion,
+        },
+        ..Default::default()
+    };
+    // fill and stroke are faded separately, which only shows where they
+    // overlap
+    let color = |s: &str| {
+        color(s).map(|mut c| {
+            c.apply_opacity(style.opacity);
+            c
+        })
+    };
+    if let Some(fill) = style.fill.as_deref().and_then(color) {
+        paint.set_color(fill);
+        pixmap.fill_path(path, &paint, FillRule::Winding, transform, None);
+    }
+    if let Some(stroke) = style.stroke.as_deref().and_then(color) {
+        paint.set_color(stroke);
+        let stroke = Stroke {
+            width: style.stroke_width,
+            line_cap: if style.round_caps {
+                LineCap::Round
+            } else {
+                LineCap::Butt
+            },
+            ..Default::default()
+        };
+        pixmap.stroke_path(path, &paint, &stroke, transform, None);
+    }
+}
+
+/// A rectangle with corners rounded by `rx`, approximated with cubic arcs.
+fn rounded_rect(at: Point, width: f32, height: f32, rx: f32) -> Option<tiny_skia::Path> {
+    let rx = rx.min(width / 2.0).min(height / 2.0);
+    if rx <= 0.0 {
+        return Rect::from_xywh(at.x, at.y, width, height).map(PathBuilder::from_rect);
+    }
+    // distance of the control points to approximate a quarter circle
+    let k = rx * 0.552_284_8;
+    let (x0, y0, x1, y1) = (at.x, at.y, at.x + width, at.y + height);
+    let mut pb = PathBuilder::new();
+    pb.move_to(x0 + rx, y0);
+    pb.line_to(x1 - rx, y0);
+    pb.cubic_to(x1 - rx + k, y0, x1, y0 + rx - k, x1, y0 + rx);
+    pb.line_to(x1, y1 - rx);
+    pb.cubic_to(x1, y1 - rx + k, x1 - rx + k, y1, x1 - rx, y1);
+    pb.line_to(x0 + rx, y1);
+    pb.cubic_to(x0 + rx - k, y1, x0, y1 - rx + k, x0, y1 - rx);
+    pb.line_to(x0, y0 + rx);
+    pb.cubic_to(x0, y0 + rx - k, x0 + rx - k, y0, x0 + rx, y0);
+    pb.close();
+    pb.finish()
+}
+
+fn color(s: &str) -> Option<Color> {
+    rgb(s).map(|[r, g, b]| Color::from_rgba8(r, g, b, 255))
+}
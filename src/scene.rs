@@ -0,0 +1,214 @@
+//! A renderer-neutral description of a genny drawing.
+//!
+//! Generators produce a [`Scene`], and each frontend (the Yew app, SVG
+//! serialization, ...) decides how to turn it into pixels or markup. The
+//! model mirrors the small subset of SVG that genny needs: a canvas size,
+//! reusable symbols and a list of shapes.
+
+use crate::config::BlendMode;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance_to(&self, p: &Point) -> f32 {
+        ((self.x - p.x).powi(2) + (self.y - p.y).powi(2)).sqrt()
+    }
+
+    pub fn from_usize(x: usize, y: usize) -> Point {
+        Point {
+            x: x as f32,
+            y: y as f32,
+        }
+    }
+}
+
+/// Presentation attributes of a shape. A `None` is inherited from whatever
+/// references the shape, exactly like an absent SVG attribute.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Style {
+    pub fill: Option<String>,
+    pub stroke: Option<String>,
+    pub stroke_width: Option<f32>,
+    pub round_caps: bool,
+    /// 0 to 1, applied to fill and stroke together.
+    pub opacity: Option<f32>,
+    pub blend: Option<BlendMode>,
+    /// Shapes sharing a pen end up on the same plotter layer. Without one,
+    /// shapes are grouped by stroke color.
+    pub pen: Option<String>,
+}
+
+impl Style {
+    pub fn fill(color: &str) -> Style {
+        Style {
+            fill: Some(color.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    pub fn stroke(color: &str, width: f32) -> Style {
+        Style {
+            stroke: Some(color.to_owned()),
+            stroke_width: Some(width),
+            fill: Some("transparent".to_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCmd {
+    MoveTo(Point),
+    LineTo(Point),
+    /// A quadratic Bézier curve: its control point, then where it ends.
+    QuadTo(Point, Point),
+    /// A cubic Bézier curve: its two control points, then where it ends.
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Circle {
+        center: Point,
+        r: f32,
+        style: Style,
+    },
+    Rect {
+        at: Point,
+        width: f32,
+        height: f32,
+        rx: f32,
+        style: Style,
+    },
+    Path {
+        cmds: Vec<PathCmd>,
+        style: Style,
+    },
+    /// An instance of a [`Symbol`], translated to `at`.
+    Use {
+        symbol: String,
+        at: Point,
+        style: Style,
+    },
+    /// Shapes rotated by `degrees` around `center`.
+    Rotated {
+        degrees: f32,
+        center: Point,
+        shapes: Vec<Shape>,
+    },
+}
+
+impl Shape {
+    fn map_colors(&self, f: &impl Fn(&str) -> String) -> Shape {
+        let mut shape = self.clone();
+        match &mut shape {
+            Shape::Circle { style, .. }
+            | Shape::Rect { style, .. }
+            | Shape::Path { style, .. }
+            | Shape::Use { style, .. } => {
+                style.fill = style.fill.as_deref().map(f);
+                style.stroke = style.stroke.as_deref().map(f);
+            }
+            Shape::Rotated { shapes, .. } => {
+                *shapes = shapes.iter().map(|s| s.map_colors(f)).collect();
+            }
+        }
+        shape
+    }
+}
+
+/// A named group of shapes that can be instanced many times with
+/// [`Shape::Use`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub id: String,
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scene {
+    pub width: f32,
+    pub height: f32,
+    /// Color painted under every shape; `None` leaves the canvas transparent.
+    pub background: Option<String>,
+    pub symbols: Vec<Symbol>,
+    pub shapes: Vec<Shape>,
+}
+
+impl Scene {
+    pub fn new(width: f32, height: f32) -> Scene {
+        Scene {
+            width,
+            height,
+            background: Some("#ffffff".to_owned()),
+            symbols: vec![],
+            shapes: vec![],
+        }
+    }
+
+    pub fn symbol(&self, id: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| s.id == id)
+    }
+
+    /// A copy with every fill, stroke and background color passed through
+    /// `f`. Pens are kept, so plotter layers don't change.
+    pub fn map_colors(&self, f: &impl Fn(&str) -> String) -> Scene {
+        Scene {
+            background: self.background.as_deref().map(f),
+            symbols: self
+                .symbols
+                .iter()
+                .map(|symbol| Symbol {
+                    id: symbol.id.clone(),
+                    shapes: symbol.shapes.iter().map(|s| s.map_colors(f)).collect(),
+                })
+                .collect(),
+            shapes: self.shapes.iter().map(|s| s.map_colors(f)).collect(),
+            ..*self
+        }
+    }
+}
+
+/// Builds the `d` attribute of an SVG path.
+pub fn path_data(cmds: &[PathCmd]) -> String {
+    cmds.iter()
+        .map(|cmd| match cmd {
+            PathCmd::MoveTo(p) => format!("M {} {}", p.x, p.y),
+            PathCmd::LineTo(p) => format!("L {} {}", p.x, p.y),
+            PathCmd::QuadTo(c, p) => format!("Q {} {} {} {}", c.x, c.y, p.x, p.y),
+            PathCmd::CubicTo(c1, c2, p) => {
+                format!("C {} {} {} {} {} {}", c1.x, c1.y, c2.x, c2.y, p.x, p.y)
+            }
+            PathCmd::Close => "Z".to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses the colors genny emits (`#rrggbb`, `#rgb` and a few names) into
+/// RGB components. `none` and `transparent` have no color.
+pub fn rgb(color: &str) -> Option<[u8; 3]> {
+    match color.to_lowercase().as_str() {
+        "black" => return Some([0, 0, 0]),
+        "white" => return Some([255, 255, 255]),
+        "red" => return Some([255, 0, 0]),
+        "gold" => return Some([255, 215, 0]),
+        _ => {}
+    }
+    let hex = color.strip_prefix('#')?;
+    let digit = |i: usize, len: usize| u8::from_str_radix(hex.get(i..i + len)?, 16).ok();
+    match hex.len() {
+        6 => Some([digit(0, 2)?, digit(2, 2)?, digit(4, 2)?]),
+        3 => Some([digit(0, 1)? * 17, digit(1, 1)? * 17, digit(2, 1)? * 17]),
+        _ => None,
+    }
+}
@@ -0,0 +1,443 @@
+use crate::config::{
+    ClusterColoring, ColorDrift, Contour, LchChannel, LinkBias, Links, Mode, ModelProperties,
+    NoiseKind, Tiling, Variant,
+};
+use crate::contour;
+use crate::disjoint_set::DisjointSet;
+use crate::scene::{Point, Shape, Style};
+use crate::tiling::{self, Cell, CellShape, Direction};
+use noise::{NoiseFn, OpenSimplex, Perlin, Seedable, Worley};
+use palette::encoding::Srgb;
+use palette::rgb::Rgb;
+use palette::FromColor;
+use palette::Lch;
+use palette::Pixel;
+use palette::Srgb as SrgbColor;
+use rand::{rngs::StdRng, Rng};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+};
+
+/// A cell with its links, and the cluster they make it part of.
+struct ClusteredCell {
+    p: Point,
+    shape: CellShape,
+    /// Whether the cell is linked across each of its sides.
+    links: Vec<bool>,
+    cluster_id: usize,
+    cluster_size: usize,
+}
+
+impl ClusteredCell {
+    fn draw(
+        &self,
+        colors: &[String],
+        variant: Variant,
+        coloring: &Coloring,
+        drift: &Drift,
+    ) -> Vec<Shape> {
+        let (pen, color) = self.color(colors, coloring, drift);
+        let pen = Some(pen);
+        let style = match variant {
+            Variant::Filled => Style {
+                stroke: Some(color.clone()),
+                fill: Some(color),
+                pen,
+                ..Default::default()
+            },
+            Variant::Outline | Variant::Contour => Style {
+                stroke: Some(color),
+                pen,
+                ..Default::default()
+            },
+        };
+        tiling::draw(self.p, self.shape, &self.links, variant, &style)
+    }
+
+    /// The palette color picked for the cell, which names its pen, and that
+    /// color after the drift. Colors that aren't hex don't drift.
+    fn color(&self, colors: &[String], coloring: &Coloring, drift: &Drift) -> (String, String) {
+        let color = colors[coloring.index(self, colors.len())].to_owned();
+        let color2: Rgb<Srgb, u8> = match Rgb::from_str(&color) {
+            Ok(rgb) => rgb,
+            Err(_) => return (color.clone(), color),
+        };
+        let color2 = color2.into_format::<f32>();
+        let color2 = drift.apply(Lch::from_color(color2), self.p);
+
+        let color2 = SrgbColor::from_color(color2);
+        let drifted: [u8; 3] = color2.into_format().into_raw();
+        let drifted = format!("#{:0>2x}{:0>2x}{:0>2x}", drifted[0], drifted[1], drifted[2]);
+        (color, drifted)
+    }
+
+    /// How many neighbours the cell is linked to.
+    fn degree(&self) -> usize {
+        self.links.iter().filter(|link| **link).count()
+    }
+}
+
+/// A [`ColorDrift`] ready to sample over a canvas.
+struct Drift {
+    settings: ColorDrift,
+    noise: Box<dyn NoiseFn<[f64; 2]>>,
+    width: f32,
+    height: f32,
+}
+
+impl Drift {
+    fn new(settings: ColorDrift, p: &ModelProperties) -> Drift {
+        // the drawing's rng isn't touched so that the squares themselves
+        // don't change with the drift settings
+        let seed = noise_seed(p);
+        let noise: Box<dyn NoiseFn<[f64; 2]>> = match settings.noise {
+            NoiseKind::Perlin => Box::new(Perlin::new().set_seed(seed)),
+            NoiseKind::OpenSimplex => Box::new(OpenSimplex::new().set_seed(seed)),
+            NoiseKind::Worley => Box::new(Worley::new().set_seed(seed)),
+        };
+        Drift {
+            settings,
+            noise,
+            width: p.width() as f32,
+            height: p.height() as f32,
+        }
+    }
+
+    /// Noise at `at`, roughly within -1 to 1.
+    fn sample(&self, at: Point) -> f32 {
+        let x = (at.x / self.width * self.settings.frequency) as f64;
+        let y = (at.y / self.height * self.settings.frequency) as f64;
+        let (mut total, mut weight) = (0.0, 0.0);
+        for octave in 0..self.settings.octaves.max(1) {
+            let scale = 2f64.powi(octave as i32);
+            total += self.noise.get([x * scale, y * scale]) / scale;
+            weight += 1.0 / scale;
+        }
+        (total / weight) as f32
+    }
+
+    fn apply(&self, mut color: Lch, at: Point) -> Lch {
+        let change = self.sample(at) * self.settings.amount;
+        match self.settings.channel {
+            LchChannel::Hue => color.hue += change,
+            LchChannel::Chroma => color.chroma = (color.chroma + change).max(0.0),
+            LchChannel::Lightness => color.l = (color.l + change).clamp(0.0, 100.0),
+        }
+        color
+    }
+}
+
+/// Seeds noise from the drawing's seed, so that it changes along with the
+/// rest of the drawing.
+pub(crate) fn noise_seed(p: &ModelProperties) -> u32 {
+    (p.seed ^ (p.seed >> 32)) as u32
+}
+
+/// Where a square's color comes from, with what [`ClusterColoring`] needs to
+/// know about the whole grid.
+struct Coloring {
+    strategy: ClusterColoring,
+    max_cluster_size: usize,
+    /// Cluster sizes, one per cluster, smallest first.
+    sizes: Vec<usize>,
+    /// The shape of each cluster, by id from 1.
+    shapes: Vec<ClusterShape>,
+    center: Point,
+}
+
+/// The kinds of cluster told apart by [`ClusterColoring::Shape`].
+#[derive(Clone, Copy)]
+enum ClusterShape {
+    Lone,
+    Straight,
+    Bent,
+    Branching,
+}
+
+impl Coloring {
+    fn new(
+        strategy: ClusterColoring,
+        clusters: &[Vec<&ClusteredCell>],
+        p: &ModelProperties,
+    ) -> Coloring {
+        let mut sizes: Vec<usize> = clusters.iter().map(|c| c.len()).collect();
+        sizes.sort_unstable();
+        let shapes = clusters
+            .iter()
+            .map(|cluster| {
+                if cluster.len() == 1 {
+                    ClusterShape::Lone
+                } else if cluster.iter().any(|square| square.degree() > 2) {
+                    ClusterShape::Branching
+                } else if cluster
+                    .iter()
+                    .flat_map(|square| {
+                        (0..square.links.len())
+                            .filter(|side| square.links[*side])
+                            .map(|side| square.shape.axis(side))
+                    })
+                    .collect::<HashSet<_>>()
+                    .len()
+                    == 1
+                {
+                    ClusterShape::Straight
+                } else {
+                    ClusterShape::Bent
+                }
+            })
+            .collect();
+        Coloring {
+            strategy,
+            // an empty grid has no squares to color
+            max_cluster_size: sizes.last().copied().unwrap_or(0),
+            sizes,
+            shapes,
+            center: Point::new(p.width() as f32 / 2.0, p.height() as f32 / 2.0),
+        }
+    }
+
+    /// Which of `n` colors `square` is drawn with.
+    fn index(&self, square: &ClusteredCell, n: usize) -> usize {
+        match self.strategy {
+            ClusterColoring::Size => bucket(square.cluster_size, self.max_cluster_size, n),
+            ClusterColoring::Id => square.cluster_id % n,
+            ClusterColoring::Shape => spread(self.shapes[square.cluster_id - 1] as usize, 4, n),
+            ClusterColoring::Degree => spread(square.degree(), square.shape.sides() + 1, n),
+            ClusterColoring::Distance => {
+                let distance = |p: Point| (p.x - self.center.x).hypot(p.y - self.center.y);
+                // the corners are the farthest squares can be
+                let fraction = distance(square.p) / distance(Point::new(0.0, 0.0));
+                ((fraction * n as f32) as usize).min(n - 1)
+            }
+            ClusterColoring::Quantile => {
+                // the share of clusters smaller than this one
+                let smaller = self
+                    .sizes
+                    .partition_point(|size| *size < square.cluster_size);
+                smaller * n / self.sizes.len()
+            }
+        }
+    }
+}
+
+/// Spreads `kinds` categories over `n` colors, so that the first and last
+/// kinds get the first and last colors.
+fn spread(kind: usize, kinds: usize, n: usize) -> usize {
+    (kind * (n - 1) + (kinds - 1) / 2) / (kinds - 1)
+}
+
+pub fn render_squares(p: &ModelProperties, rng: &mut StdRng) -> Vec<Shape> {
+    let props = match p.mode {
+        Mode::Squares(props) => props,
+        Mode::Strings(_) | Mode::Maze(_) => Default::default(),
+    };
+    let drift = Drift::new(props.drift, p);
+    let squares = create_squares(p, props.tiling, &props.links, rng);
+    let clusters = group(&squares);
+    let coloring = Coloring::new(props.coloring, &clusters, p);
+    let colors = p.colors();
+    if p.variant == Variant::Contour {
+        return contours(&clusters, p, props.contour, &colors, &coloring, &drift);
+    }
+    squares
+        .iter()
+        .flat_map(|square| square.draw(&colors, p.variant, &coloring, &drift))
+        .collect()
+}
+
+/// One path around each cluster, in the color of its first cell. Clusters
+/// with holes get a loop around each of them too.
+fn contours(
+    clusters: &[Vec<&ClusteredCell>],
+    p: &ModelProperties,
+    settings: Contour,
+    colors: &[String],
+    coloring: &Coloring,
+    drift: &Drift,
+) -> Vec<Shape> {
+    clusters
+        .iter()
+        .map(|cluster| {
+            let segments: Vec<(Point, Point)> = cluster
+                .iter()
+                .flat_map(|square| {
+                    tiling::boundary(square.p, square.shape, &square.links, &p.cells)
+                })
+                .collect();
+            let (pen, color) = cluster[0].color(colors, coloring, drift);
+            Shape::Path {
+                cmds: contour::trace(&segments)
+                    .iter()
+                    .flat_map(|points| {
+                        contour::path(&contour::offset(points, settings.offset), settings.radius)
+                    })
+                    .collect(),
+                style: Style {
+                    stroke: Some(color),
+                    stroke_width: Some(p.cells.stroke_width),
+                    pen: Some(pen),
+                    ..Default::default()
+                },
+            }
+        })
+        .collect()
+}
+
+/// The cells of each cluster, by id from 1.
+fn group(squares: &[ClusteredCell]) -> Vec<Vec<&ClusteredCell>> {
+    let mut clusters: Vec<Vec<&ClusteredCell>> = vec![];
+    for square in squares {
+        if clusters.len() < square.cluster_id {
+            clusters.resize(square.cluster_id, vec![]);
+        }
+        clusters[square.cluster_id - 1].push(square);
+    }
+    clusters
+}
+
+fn create_squares(
+    p: &ModelProperties,
+    tiling: Tiling,
+    links: &Links,
+    rng: &mut StdRng,
+) -> Vec<ClusteredCell> {
+    let cells = tiling::grid(p, tiling);
+    let bias = link_bias(p, links.bias);
+    let mut linked: Vec<Vec<bool>> = cells
+        .iter()
+        .map(|cell| vec![false; cell.shape.sides()])
+        .collect();
+    for (i, cell) in cells.iter().enumerate() {
+        let bias = bias(cell.at);
+        for &(side, direction) in cell.shape.owned() {
+            let chance = match direction {
+                Direction::Right => links.right,
+                Direction::Down => links.down,
+            };
+            let link = rng.gen::<f32>() < (chance * bias).clamp(0.0, 1.0);
+            if let (true, Some(neighbour)) = (link, cell.neighbours[side]) {
+                set_link(&cells, &mut linked, i, neighbour, true);
+            }
+        }
+    }
+    if let Some(max) = links.max_cluster_size {
+        cap_clusters(&cells, &mut linked, max.max(1));
+    }
+    let mut clusters = DisjointSet::new(cells.len());
+    for i in 0..cells.len() {
+        for neighbour in linked_to(&cells, &linked, i) {
+            clusters.union(i, neighbour);
+        }
+    }
+    // ids count up from 1 in the order of the first cell of each cluster
+    let mut ids: HashMap<usize, usize> = HashMap::new();
+    cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let root = clusters.find(i);
+            let next_id = ids.len() + 1;
+            ClusteredCell {
+                p: cell.at,
+                shape: cell.shape,
+                links: linked[i].clone(),
+                cluster_id: *ids.entry(root).or_insert(next_id),
+                cluster_size: clusters.size(root),
+            }
+        })
+        .collect()
+}
+
+/// Links or unlinks neighbours `a` and `b`, on both of their sides.
+fn set_link(cells: &[Cell], linked: &mut [Vec<bool>], a: usize, b: usize, value: bool) {
+    for (from, to) in [(a, b), (b, a)] {
+        let side = cells[from]
+            .neighbours
+            .iter()
+            .position(|n| *n == Some(to))
+            .expect("only neighbours are linked");
+        linked[from][side] = value;
+    }
+}
+
+/// The cells `i` is linked to.
+fn linked_to(cells: &[Cell], linked: &[Vec<bool>], i: usize) -> Vec<usize> {
+    cells[i]
+        .neighbours
+        .iter()
+        .zip(&linked[i])
+        .filter_map(|(neighbour, link)| neighbour.filter(|_| *link))
+        .collect()
+}
+
+/// How much more likely than usual squares at a point are to link, from 0 to
+/// 2.
+fn link_bias(p: &ModelProperties, bias: LinkBias) -> Box<dyn Fn(Point) -> f32> {
+    let (width, height) = (p.width() as f32, p.height() as f32);
+    // 0 at the center, 1 in the corners
+    let distance =
+        move |at: Point| (at.x / width - 0.5).hypot(at.y / height - 0.5) / 0.5f32.hypot(0.5);
+    match bias {
+        LinkBias::Uniform => Box::new(|_| 1.0),
+        LinkBias::Center => Box::new(move |at| 2.0 * (1.0 - distance(at))),
+        LinkBias::Edges => Box::new(move |at| 2.0 * distance(at)),
+        LinkBias::Noise => {
+            // a different seed than the color drift, so the two don't line up
+            let perlin = Perlin::new().set_seed(noise_seed(p).wrapping_add(1));
+            Box::new(move |at| {
+                let value = perlin.get([(at.x / width * 3.0) as f64, (at.y / height * 3.0) as f64]);
+                (1.0 + value as f32).clamp(0.0, 2.0)
+            })
+        }
+    }
+}
+
+/// Cuts links so that no cluster has more than `max` cells. Clusters are
+/// grown breadth-first from their first cell, and links leaving a full
+/// cluster are dropped; the cells left out start clusters of their own.
+fn cap_clusters(cells: &[Cell], linked: &mut [Vec<bool>], max: usize) {
+    let mut visited = vec![false; cells.len()];
+    for start in 0..cells.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut cluster: HashSet<usize> = HashSet::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            if cluster.len() == max {
+                break;
+            }
+            if visited[i] {
+                continue;
+            }
+            visited[i] = true;
+            cluster.insert(i);
+            queue.extend(linked_to(cells, linked, i));
+        }
+        for &i in cluster.iter() {
+            for neighbour in linked_to(cells, linked, i) {
+                if !cluster.contains(&neighbour) {
+                    set_link(cells, linked, i, neighbour, false);
+                }
+            }
+        }
+    }
+}
+
+/// Picks one of `n` colors for a cluster: the first for lone squares, the
+/// last for the largest cluster, and the ones in between by size relative to
+/// the largest. With five colors the middle thresholds are 2/5 and 3/5.
+fn bucket(cluster_size: usize, max_cluster_size: usize, n: usize) -> usize {
+    if n == 1 || cluster_size == 1 {
+        0
+    } else if cluster_size == max_cluster_size || n == 2 {
+        n - 1
+    } else {
+        let middle = n - 2;
+        let fraction = cluster_size as f32 / max_cluster_size as f32;
+        1 + (1..middle)
+            .filter(|k| fraction >= (k + 1) as f32 / (middle + 2) as f32)
+            .count()
+    }
+}
@@ -0,0 +1,226 @@
+use crate::config::{
+    Bend, BlendMode, LayerColor, ModelProperties, StringCurve, StringDirection, StringLayer,
+    StringsModeProps,
+};
+use crate::outlines;
+use crate::scene::{PathCmd, Point, Shape, Style};
+use crate::squares::noise_seed;
+use noise::{NoiseFn, OpenSimplex, Seedable};
+use palette::encoding::Srgb;
+use palette::rgb::Rgb;
+use palette::{FromColor, Lch, Mix, Pixel, Srgb as SrgbColor};
+use std::str::FromStr;
+
+pub fn render_strings(p: &ModelProperties, props: &StringsModeProps) -> Vec<Shape> {
+    let splits = props.splits;
+    let show_base = props.show_base;
+    let colors = p.colors();
+
+    let mut res = vec![];
+    let inner_points = outlines::points(&props.inner, splits, p);
+    let outer_points = outlines::points(&props.outer, splits, p);
+    if show_base {
+        res.push(render_outline(
+            outlines::outline(&props.inner, p),
+            Style::fill("red"),
+        ));
+        res.push(render_outline(
+            outlines::outline(&props.outer, p),
+            Style {
+                stroke: Some("red".to_owned()),
+                ..Default::default()
+            },
+        ));
+        for p in inner_points.iter().chain(outer_points.iter()) {
+            res.push(render_point(*p));
+        }
+    }
+
+    let bender = Bender::new(props.curve, p);
+    for layer in props.layers.iter() {
+        res.extend(render_layer(
+            &inner_points,
+            &outer_points,
+            layer,
+            &colors,
+            &bender,
+        ));
+    }
+    res
+}
+
+/// The string of `layer`, from each outer point back to an inner one
+/// `aperture` points further along the way the string goes.
+fn render_layer(
+    inner: &[(f32, f32)],
+    outer: &[(f32, f32)],
+    layer: &StringLayer,
+    colors: &[String],
+    bender: &Bender,
+) -> Vec<Shape> {
+    let n = inner.len().min(outer.len());
+    let shift = layer.aperture % n.max(1);
+    let mut path = vec![];
+    for m in 0..n {
+        let (m, i) = match layer.direction {
+            StringDirection::Forward => (m, (m + shift) % n),
+            StringDirection::Backward => (n - 1 - m, (2 * n - 1 - m - shift) % n),
+        };
+        path.push(Point::new(inner[i].0, inner[i].1));
+        path.push(Point::new(outer[m].0, outer[m].1));
+    }
+    let style = |color: String, pen: &str| Style {
+        opacity: (layer.opacity < 1.0).then_some(layer.opacity),
+        blend: (layer.blend != BlendMode::Normal).then_some(layer.blend),
+        pen: (color != pen).then(|| pen.to_owned()),
+        ..Style::stroke(&color, layer.stroke_width)
+    };
+    match layer.color {
+        LayerColor::Palette(at) => {
+            let color = palette_color(colors, at);
+            let mut cmds: Vec<PathCmd> = path
+                .first()
+                .map(|p| PathCmd::MoveTo(*p))
+                .into_iter()
+                .collect();
+            cmds.extend(path.windows(2).map(|w| bender.step(w[0], w[1])));
+            // straight strings are closed by the close command alone
+            if let (Some(last), Some(first)) = (path.last(), path.first()) {
+                if bender.curve.bend != Bend::Straight {
+                    cmds.push(bender.step(*last, *first));
+                }
+            }
+            cmds.push(PathCmd::Close);
+            vec![Shape::Path {
+                cmds,
+                style: style(color.clone(), &color),
+            }]
+        }
+        // a line per step, each a little further through the palette
+        LayerColor::Gradient(from, to) => {
+            let steps = path.len();
+            (0..steps)
+                .map(|k| {
+                    let at = from + (to - from) * k as f32 / (steps - 1).max(1) as f32;
+                    let (a, b) = (path[k], path[(k + 1) % steps]);
+                    Shape::Path {
+                        cmds: vec![PathCmd::MoveTo(a), bender.step(a, b)],
+                        style: style(gradient_color(colors, at), &palette_color(colors, at)),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Turns the steps of strings into lines or curves.
+struct Bender {
+    curve: StringCurve,
+    noise: OpenSimplex,
+    width: f32,
+    height: f32,
+}
+
+impl Bender {
+    fn new(curve: StringCurve, p: &ModelProperties) -> Bender {
+        Bender {
+            curve,
+            noise: OpenSimplex::new().set_seed(noise_seed(p)),
+            width: p.width() as f32,
+            height: p.height() as f32,
+        }
+    }
+
+    /// How to draw the step from `a` to `b`. Cubic curves bend one way, then
+    /// the other, into an S.
+    fn step(&self, a: Point, b: Point) -> PathCmd {
+        let along = |t: f32| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+        let strength = self.curve.strength;
+        match (self.curve.bend, self.curve.cubic) {
+            (Bend::Straight, _) => PathCmd::LineTo(b),
+            (_, false) => PathCmd::QuadTo(self.control(along(0.5), strength), b),
+            (_, true) => PathCmd::CubicTo(
+                self.control(along(1.0 / 3.0), strength),
+                self.control(along(2.0 / 3.0), -strength),
+                b,
+            ),
+        }
+    }
+
+    /// Where `at`, on the straight line, goes as a control point.
+    fn control(&self, at: Point, strength: f32) -> Point {
+        match self.curve.bend {
+            Bend::Straight => at,
+            Bend::Attractor => {
+                let (x, y) = self.curve.attractor;
+                let (x, y) = (x * self.width, y * self.height);
+                Point::new(at.x + (x - at.x) * strength, at.y + (y - at.y) * strength)
+            }
+            // a quarter turn at full strength
+            Bend::Twist => {
+                let (cx, cy) = (self.width / 2.0, self.height / 2.0);
+                let (sin, cos) = (strength * std::f32::consts::FRAC_PI_2).sin_cos();
+                let (dx, dy) = (at.x - cx, at.y - cy);
+                Point::new(cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+            }
+            // up to half the canvas away at full strength
+            Bend::Noise => {
+                let x = (at.x / self.width * self.curve.frequency) as f64;
+                let y = (at.y / self.height * self.curve.frequency) as f64;
+                // far apart samples, so that both ways are independent
+                let dx = self.noise.get([x, y]) as f32;
+                let dy = self.noise.get([x + 100.0, y + 100.0]) as f32;
+                let reach = strength * self.width / 2.0;
+                Point::new(at.x + dx * reach, at.y + dy * reach)
+            }
+        }
+    }
+}
+
+/// The palette color closest to `at`, 0 being the first color and 1 the
+/// last.
+fn palette_color(colors: &[String], at: f32) -> String {
+    let last = colors.len() - 1;
+    colors[((at.clamp(0.0, 1.0) * last as f32).round() as usize).min(last)].clone()
+}
+
+/// The color at `at` when the palette colors blend into each other, mixed
+/// in LCH.
+fn gradient_color(colors: &[String], at: f32) -> String {
+    let lch = |color: &str| {
+        let rgb: Rgb<Srgb, u8> = Rgb::from_str(color).unwrap_or_default();
+        Lch::from_color(rgb.into_format::<f32>())
+    };
+    let position = at.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+    let before = position.floor() as usize;
+    let after = (before + 1).min(colors.len() - 1);
+    let mixed = lch(&colors[before]).mix(&lch(&colors[after]), position - before as f32);
+    let rgb: [u8; 3] = SrgbColor::from_color(mixed).into_format().into_raw();
+    format!("#{:0>2x}{:0>2x}{:0>2x}", rgb[0], rgb[1], rgb[2])
+}
+
+fn render_outline(path: Vec<(f32, f32)>, style: Style) -> Shape {
+    let mut cmds: Vec<PathCmd> = path
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let p = Point::new(p.0, p.1);
+            if i == 0 {
+                PathCmd::MoveTo(p)
+            } else {
+                PathCmd::LineTo(p)
+            }
+        })
+        .collect();
+    // close the path
+    cmds.push(PathCmd::Close);
+    Shape::Path { cmds, style }
+}
+
+fn render_point(p: (f32, f32)) -> Shape {
+    Shape::Circle {
+        center: Point::new(p.0, p.1),
+        r: 1.0,
+        style: Style::fill("black"),
+    }
+}
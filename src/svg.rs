@@ -0,0 +1,148 @@
+//! Serializes a [`Scene`] into a standalone SVG document, matching what the
+//! web app draws. Symbols are referenced through `xlink:href` and the canvas
+//! has explicit dimensions, so the file opens the same way in browsers,
+//! Inkscape and Illustrator.
+
+use crate::scene::{path_data, Scene, Shape, Style};
+use std::fmt::Write;
+
+pub fn to_svg(scene: &Scene) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    writeln!(
+        out,
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" "#,
+            r#"version="1.1" width="{w}" height="{h}" viewBox="0 0 {w} {h}" fill="none">"#
+        ),
+        w = scene.width,
+        h = scene.height
+    )
+    .unwrap();
+    out.push_str("<defs>\n");
+    for symbol in scene.symbols.iter() {
+        writeln!(out, r#"<g id="{}">"#, escape(&symbol.id)).unwrap();
+        for shape in symbol.shapes.iter() {
+            write_shape(&mut out, shape);
+        }
+        out.push_str("</g>\n");
+    }
+    out.push_str("</defs>\n");
+    if let Some(background) = &scene.background {
+        writeln!(
+            out,
+            r#"<rect x="0" y="0" width="{}" height="{}" fill="{}"/>"#,
+            scene.width,
+            scene.height,
+            escape(background)
+        )
+        .unwrap();
+    }
+    for shape in scene.shapes.iter() {
+        write_shape(&mut out, shape);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn write_shape(out: &mut String, shape: &Shape) {
+    match shape {
+        Shape::Circle { center, r, style } => {
+            writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}"{}/>"#,
+                center.x,
+                center.y,
+                r,
+                style_attrs(style)
+            )
+            .unwrap();
+        }
+        Shape::Rect {
+            at,
+            width,
+            height,
+            rx,
+            style,
+        } => {
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" ry="{}"{}/>"#,
+                at.x,
+                at.y,
+                width,
+                height,
+                rx,
+                rx,
+                style_attrs(style)
+            )
+            .unwrap();
+        }
+        Shape::Path { cmds, style } => {
+            writeln!(
+                out,
+                r#"<path d="{}"{}/>"#,
+                path_data(cmds),
+                style_attrs(style)
+            )
+            .unwrap();
+        }
+        Shape::Use { symbol, at, style } => {
+            writeln!(
+                out,
+                r##"<use x="{}" y="{}" xlink:href="#{}"{}/>"##,
+                at.x,
+                at.y,
+                escape(symbol),
+                style_attrs(style)
+            )
+            .unwrap();
+        }
+        Shape::Rotated {
+            degrees,
+            center,
+            shapes,
+        } => {
+            writeln!(
+                out,
+                r#"<g transform="rotate({},{},{})">"#,
+                degrees, center.x, center.y
+            )
+            .unwrap();
+            for shape in shapes.iter() {
+                write_shape(out, shape);
+            }
+            out.push_str("</g>\n");
+        }
+    }
+}
+
+fn style_attrs(style: &Style) -> String {
+    let mut attrs = String::new();
+    if let Some(fill) = &style.fill {
+        write!(attrs, r#" fill="{}""#, escape(fill)).unwrap();
+    }
+    if let Some(stroke) = &style.stroke {
+        write!(attrs, r#" stroke="{}""#, escape(stroke)).unwrap();
+    }
+    if let Some(width) = style.stroke_width {
+        write!(attrs, r#" stroke-width="{}""#, width).unwrap();
+    }
+    if style.round_caps {
+        attrs.push_str(r#" stroke-linecap="round""#);
+    }
+    if let Some(opacity) = style.opacity {
+        write!(attrs, r#" opacity="{}""#, opacity).unwrap();
+    }
+    if let Some(blend) = style.blend {
+        write!(attrs, r#" style="mix-blend-mode:{}""#, blend.css()).unwrap();
+    }
+    attrs
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,525 @@
+//! The lattices Squares mode lays its cells out on: squares, hexagons or
+//! triangles, each cell linking to the neighbours across its sides.
+
+use crate::config::{Cells, ModelProperties, Tiling, Variant};
+use crate::scene::{PathCmd, Point, Shape, Style, Symbol};
+
+/// Which link chance applies to a side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Direction {
+    Right,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CellShape {
+    /// Sides up, left, right and down.
+    Square,
+    /// Pointy-top, with sides clockwise from the upper right one.
+    Hex,
+    /// Sides right, bottom and left.
+    TriangleUp,
+    /// Sides top, right and left.
+    TriangleDown,
+}
+
+impl CellShape {
+    pub(crate) fn sides(self) -> usize {
+        match self {
+            CellShape::Square => 4,
+            CellShape::Hex => 6,
+            CellShape::TriangleUp | CellShape::TriangleDown => 3,
+        }
+    }
+
+    /// The sides whose links the cell decides on, in that order. Links
+    /// across the other sides are up to neighbours earlier in the grid.
+    pub(crate) fn owned(self) -> &'static [(usize, Direction)] {
+        match self {
+            CellShape::Square => &[(2, Direction::Right), (3, Direction::Down)],
+            CellShape::Hex => &[
+                (1, Direction::Right),
+                (2, Direction::Down),
+                (3, Direction::Down),
+            ],
+            CellShape::TriangleUp => &[(0, Direction::Right), (1, Direction::Down)],
+            CellShape::TriangleDown => &[(1, Direction::Right)],
+        }
+    }
+
+    /// Sides linking along the same line share an axis.
+    pub(crate) fn axis(self, side: usize) -> usize {
+        match self {
+            CellShape::Square => (side == 0 || side == 3) as usize,
+            CellShape::Hex => side % 3,
+            CellShape::TriangleUp => (side == 1) as usize,
+            CellShape::TriangleDown => (side == 0) as usize,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            CellShape::Square => "square",
+            CellShape::Hex => "hex",
+            CellShape::TriangleUp => "triangle_up",
+            CellShape::TriangleDown => "triangle_down",
+        }
+    }
+
+    /// Each side as a segment going clockwise around the cell, relative to
+    /// where the cell is drawn.
+    fn edges(self, cells: &Cells) -> Vec<((f32, f32), (f32, f32))> {
+        let size = cells.size as f32;
+        match self {
+            CellShape::Square => vec![
+                ((0.0, 0.0), (size, 0.0)),
+                ((0.0, size), (0.0, 0.0)),
+                ((size, 0.0), (size, size)),
+                ((size, size), (0.0, size)),
+            ],
+            _ => {
+                let corners = self.corners((cells.size + cells.gap) as f32, cells.gap as f32);
+                (0..corners.len())
+                    .map(|side| (corners[side], corners[(side + 1) % corners.len()]))
+                    .collect()
+            }
+        }
+    }
+
+    /// Corners clockwise around the center, the first one starting side 0.
+    fn corners(self, step: f32, gap: f32) -> Vec<(f32, f32)> {
+        let sqrt3 = 3f32.sqrt();
+        match self {
+            CellShape::Square => vec![],
+            CellShape::Hex => {
+                let radius = (step - gap) / sqrt3;
+                (0..6)
+                    .map(|k| {
+                        let angle = (-90.0 + 60.0 * k as f32).to_radians();
+                        (radius * angle.cos(), radius * angle.sin())
+                    })
+                    .collect()
+            }
+            CellShape::TriangleUp | CellShape::TriangleDown => {
+                let r = step / sqrt3 - gap / 2.0;
+                if self == CellShape::TriangleUp {
+                    vec![(0.0, -2.0 * r), (sqrt3 * r, r), (-sqrt3 * r, r)]
+                } else {
+                    vec![(-sqrt3 * r, -r), (sqrt3 * r, -r), (0.0, 2.0 * r)]
+                }
+            }
+        }
+    }
+}
+
+/// A cell of the grid, and the cells across each of its sides.
+pub(crate) struct Cell {
+    /// The top left corner of squares, the center of other shapes.
+    pub at: Point,
+    pub shape: CellShape,
+    pub neighbours: Vec<Option<usize>>,
+}
+
+/// The cells of a `tiling` over the canvas, row by row.
+pub(crate) fn grid(p: &ModelProperties, tiling: Tiling) -> Vec<Cell> {
+    let step = p.step() as f32;
+    let (width, height) = (
+        p.width() as f32 - 2.0 * step,
+        p.height() as f32 - 2.0 * step,
+    );
+    let sqrt3 = 3f32.sqrt();
+    let (rows, columns, row_height) = match tiling {
+        Tiling::Square => {
+            let lattice = lattice(p);
+            (
+                lattice.len(),
+                lattice.first().map_or(0, |row| row.len()),
+                step,
+            )
+        }
+        // odd rows are shifted right by half a cell
+        Tiling::Hex => {
+            let radius = step / sqrt3;
+            let row_height = 1.5 * radius;
+            // no rows at all when not even one fits
+            let rows = match (height - 2.0 * radius) / row_height {
+                fit if fit < 0.0 => 0,
+                fit => fit.floor() as usize + 1,
+            };
+            let columns = ((width - step / 2.0) / step).floor() as usize;
+            (rows, columns, row_height)
+        }
+        // triangles half a cell apart, alternately pointing up and down
+        Tiling::Triangle => {
+            let row_height = step * sqrt3;
+            let rows = (height / row_height).floor() as usize;
+            let columns = ((width / step).floor() as usize).saturating_sub(1);
+            (rows, columns, row_height)
+        }
+    };
+    let index = |row: usize, column: usize| row * columns + column;
+    // rows and columns may be out of the grid, or -1
+    let at = |row: isize, column: isize| {
+        (row >= 0 && column >= 0 && (row as usize) < rows && (column as usize) < columns)
+            .then(|| index(row as usize, column as usize))
+    };
+    let squares: Vec<Point> = lattice(p).into_iter().flatten().collect();
+    // other tilings are centered on the canvas
+    let extent = match tiling {
+        Tiling::Square => (0.0, 0.0),
+        Tiling::Hex => (
+            columns as f32 * step + if rows > 1 { step / 2.0 } else { 0.0 },
+            2.0 * step / sqrt3 + (rows as f32 - 1.0) * row_height,
+        ),
+        Tiling::Triangle => ((columns as f32 + 1.0) * step, rows as f32 * row_height),
+    };
+    let origin = (
+        (p.width() as f32 - extent.0) / 2.0,
+        (p.height() as f32 - extent.1) / 2.0,
+    );
+    let mut cells = vec![];
+    for row in 0..rows {
+        for column in 0..columns {
+            let (r, c) = (row as isize, column as isize);
+            let cell = match tiling {
+                Tiling::Square => Cell {
+                    at: squares[index(row, column)],
+                    shape: CellShape::Square,
+                    neighbours: vec![at(r - 1, c), at(r, c - 1), at(r, c + 1), at(r + 1, c)],
+                },
+                Tiling::Hex => {
+                    let shift = (row % 2) as isize;
+                    Cell {
+                        at: Point::new(
+                            origin.0 + (column as f32 + 0.5 + shift as f32 / 2.0) * step,
+                            origin.1 + step / sqrt3 + row as f32 * row_height,
+                        ),
+                        shape: CellShape::Hex,
+                        neighbours: vec![
+                            at(r - 1, c + shift),
+                            at(r, c + 1),
+                            at(r + 1, c + shift),
+                            at(r + 1, c + shift - 1),
+                            at(r, c - 1),
+                            at(r - 1, c + shift - 1),
+                        ],
+                    }
+                }
+                Tiling::Triangle => {
+                    let x = origin.0 + (column as f32 + 1.0) * step;
+                    let top = origin.1 + row as f32 * row_height;
+                    if (row + column) % 2 == 0 {
+                        Cell {
+                            at: Point::new(x, top + row_height * 2.0 / 3.0),
+                            shape: CellShape::TriangleUp,
+                            neighbours: vec![at(r, c + 1), at(r + 1, c), at(r, c - 1)],
+                        }
+                    } else {
+                        Cell {
+                            at: Point::new(x, top + row_height / 3.0),
+                            shape: CellShape::TriangleDown,
+                            neighbours: vec![at(r - 1, c), at(r, c + 1), at(r, c - 1)],
+                        }
+                    }
+                }
+            };
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+/// A cell at `at`, linked across the sides in `links`.
+pub(crate) fn draw(
+    at: Point,
+    shape: CellShape,
+    links: &[bool],
+    variant: Variant,
+    style: &Style,
+) -> Vec<Shape> {
+    let name = shape.name();
+    match (shape, variant) {
+        (CellShape::Square, Variant::Filled) => filled(at, links[2], links[3], style),
+        (CellShape::Square, Variant::Outline) => {
+            outline(at, [links[0], links[1], links[2], links[3]], style)
+        }
+        (_, Variant::Filled) => std::iter::once(make_use(name, at, style))
+            .chain(
+                shape
+                    .owned()
+                    .iter()
+                    .filter(|(side, _)| links[*side])
+                    .map(|(side, _)| make_use(&format!("{}_link_{}", name, side), at, style)),
+            )
+            .collect(),
+        (_, Variant::Contour) => unreachable!("contours are traced around whole clusters"),
+        (_, Variant::Outline) => links
+            .iter()
+            .enumerate()
+            .map(|(side, linked)| {
+                let kind = if *linked { "connection" } else { "closed" };
+                make_use(&format!("{}_{}_{}", name, kind, side), at, style)
+            })
+            .collect(),
+    }
+}
+
+/// The symbols instanced by [`draw`] for a `tiling`.
+pub fn symbols(tiling: Tiling, cells: &Cells) -> Vec<Symbol> {
+    let shapes = match tiling {
+        Tiling::Square => return square_symbols(cells),
+        Tiling::Hex => vec![CellShape::Hex],
+        Tiling::Triangle => vec![CellShape::TriangleUp, CellShape::TriangleDown],
+    };
+    let gap = cells.gap as f32;
+    let line = line_style(cells);
+    // bridges reach a little into both cells so that no seam shows
+    let overlap = 0.5;
+    let mut symbols = vec![];
+    for shape in shapes {
+        let name = shape.name();
+        let edges = shape.edges(cells);
+        let corners: Vec<(f32, f32)> = edges.iter().map(|(a, _)| *a).collect();
+        symbols.push(path(name, &corners, true, Default::default()));
+        for (side, &(a, b)) in edges.iter().enumerate() {
+            let out = outwards(a, b);
+            symbols.push(path(
+                &format!("{}_closed_{}", name, side),
+                &[a, b],
+                false,
+                line.clone(),
+            ));
+            // the neighbour draws the other wall of the connection from its
+            // own side
+            symbols.push(path(
+                &format!("{}_connection_{}", name, side),
+                &[a, out(a, gap)],
+                false,
+                line.clone(),
+            ));
+            if shape.owned().iter().any(|(owned, _)| *owned == side) {
+                symbols.push(path(
+                    &format!("{}_link_{}", name, side),
+                    &[
+                        out(a, -overlap),
+                        out(b, -overlap),
+                        out(b, gap + overlap),
+                        out(a, gap + overlap),
+                    ],
+                    true,
+                    Default::default(),
+                ));
+            }
+        }
+    }
+    symbols
+}
+
+/// The outside of a cell at `at`, as segments going clockwise: its sides
+/// that aren't linked, and the walls of the bridges to those that are.
+/// Together with those of the rest of its cluster they make closed loops.
+pub(crate) fn boundary(
+    at: Point,
+    shape: CellShape,
+    links: &[bool],
+    cells: &Cells,
+) -> Vec<(Point, Point)> {
+    let gap = cells.gap as f32;
+    let absolute = |(x, y): (f32, f32)| Point::new(at.x + x, at.y + y);
+    shape
+        .edges(cells)
+        .into_iter()
+        .zip(links)
+        .map(|((a, b), linked)| {
+            // like the connection symbols, each cell draws the wall from the
+            // start of its side
+            let end = if *linked { outwards(a, b)(a, gap) } else { b };
+            (absolute(a), absolute(end))
+        })
+        .filter(|(a, b)| a != b)
+        .collect()
+}
+
+/// Moves points away from a cell, across its side from `a` to `b`.
+fn outwards(a: (f32, f32), b: (f32, f32)) -> impl Fn((f32, f32), f32) -> (f32, f32) {
+    let length = (b.0 - a.0).hypot(b.1 - a.1);
+    // outwards, as sides go clockwise
+    let normal = ((b.1 - a.1) / length, -(b.0 - a.0) / length);
+    move |p, by| (p.0 + normal.0 * by, p.1 + normal.1 * by)
+}
+
+/// A filled square at `at`, bridged to its right and lower neighbours.
+pub(crate) fn filled(at: Point, link_right: bool, link_down: bool, style: &Style) -> Vec<Shape> {
+    let mut res = vec![make_use("square", at, style)];
+    if link_right {
+        res.push(make_use("link_right", at, style));
+    }
+    if link_down {
+        res.push(make_use("link_down", at, style));
+    }
+    res
+}
+
+/// The sides of a square at `at`, open towards the neighbours it's linked to
+/// (up, left, right and down).
+pub(crate) fn outline(at: Point, [up, left, right, down]: [bool; 4], style: &Style) -> Vec<Shape> {
+    let top = if up { "connection_up" } else { "closed_top" };
+    let right = if right {
+        "connection_right"
+    } else {
+        "closed_right"
+    };
+    let left = if left {
+        "connection_left"
+    } else {
+        "closed_left"
+    };
+    let bottom = if down {
+        "connection_down"
+    } else {
+        "closed_bottom"
+    };
+    [top, left, right, bottom]
+        .iter()
+        .map(|href| make_use(href, at, style))
+        .collect()
+}
+
+fn make_use(href: &str, at: Point, style: &Style) -> Shape {
+    Shape::Use {
+        symbol: href.to_owned(),
+        at,
+        style: style.clone(),
+    }
+}
+
+/// Where the squares go: rows of points `p.step()` apart, leaving a margin of
+/// at least one step around the canvas.
+pub(crate) fn lattice(p: &ModelProperties) -> Vec<Vec<Point>> {
+    (0..p.height().saturating_sub(p.step()))
+        .step_by(p.step())
+        .skip(1)
+        .map(|y| {
+            (0..p.width().saturating_sub(p.step()))
+                .step_by(p.step())
+                .skip(1)
+                .map(|x| Point::from_usize(x, y))
+                .collect()
+        })
+        .collect()
+}
+
+/// Hand-drawn symbols for square cells, with the top left corner at the
+/// origin.
+fn square_symbols(cells: &Cells) -> Vec<Symbol> {
+    let size = cells.size as f32;
+    let gap = cells.gap as f32;
+    let rx = cells.corner_radius.clamp(0.0, size / 2.0);
+    // bridges start past the rounded corners of their own square and end
+    // past those of the next one
+    let (from, to) = (size - rx, size + gap + rx);
+    let line = line_style(cells);
+    vec![
+        Symbol {
+            id: "square".to_owned(),
+            shapes: vec![Shape::Rect {
+                at: Point::new(0.0, 0.0),
+                width: size,
+                height: size,
+                rx,
+                style: Default::default(),
+            }],
+        },
+        path(
+            "link_right",
+            &[(from, 0.0), (from, size), (to, size), (to, 0.0)],
+            true,
+            Default::default(),
+        ),
+        path(
+            "link_down",
+            &[(0.0, from), (0.0, to), (size, to), (size, from)],
+            true,
+            Default::default(),
+        ),
+        path(
+            "closed_top",
+            &[(0.0, 0.0), (size, 0.0)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "closed_right",
+            &[(size, 0.0), (size, size)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "closed_left",
+            &[(0.0, 0.0), (0.0, size)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "closed_bottom",
+            &[(0.0, size), (size, size)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "connection_up",
+            &[(0.0, 0.0), (0.0, -gap)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "connection_right",
+            &[(size, 0.0), (size + gap, 0.0)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "connection_left",
+            &[(0.0, size), (-gap, size)],
+            false,
+            line.clone(),
+        ),
+        path(
+            "connection_down",
+            &[(size, size), (size, size + gap)],
+            false,
+            line,
+        ),
+    ]
+}
+
+/// The style of the lines of the Outline variant.
+fn line_style(cells: &Cells) -> Style {
+    Style {
+        stroke_width: Some(cells.stroke_width),
+        round_caps: true,
+        ..Default::default()
+    }
+}
+
+fn path(id: &str, points: &[(f32, f32)], close: bool, style: Style) -> Symbol {
+    let mut cmds: Vec<PathCmd> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (x, y))| {
+            let p = Point::new(*x, *y);
+            if i == 0 {
+                PathCmd::MoveTo(p)
+            } else {
+                PathCmd::LineTo(p)
+            }
+        })
+        .collect();
+    if close {
+        cmds.push(PathCmd::Close);
+    }
+    Symbol {
+        id: id.to_owned(),
+        shapes: vec![Shape::Path { cmds, style }],
+    }
+}
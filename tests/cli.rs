@@ -0,0 +1,54 @@
+use std::process::{Command, Output};
+
+fn genny(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_genny"))
+        .args(args)
+        .output()
+        .expect("genny runs")
+}
+
+#[test]
+fn physical_sizes_are_positive_millimetres() {
+    for mm in ["0x0", "200x0", "-5x100", "200"] {
+        let out = genny(&["--mode", "Squares", "--gcode", "--mm", mm]);
+        assert!(!out.status.success(), "{} was accepted", mm);
+        let err = String::from_utf8_lossy(&out.stderr);
+        assert!(err.contains("Invalid size in millimetres"), "{}", err);
+    }
+    let out = genny(&[
+        "--mode",
+        "Squares",
+        "--seed",
+        "1",
+        "--gcode",
+        "--mm",
+        "210.5x297",
+    ]);
+    assert!(out.status.success());
+    let gcode = String::from_utf8(out.stdout).unwrap();
+    let xs: Vec<f32> = gcode
+        .lines()
+        .filter_map(|line| line.strip_prefix("G1 X"))
+        .map(|rest| rest.split(' ').next().unwrap().parse().unwrap())
+        .collect();
+    assert!(!xs.is_empty());
+    assert!(xs.iter().all(|x| (0.0..=210.5).contains(x)));
+}
+
+#[test]
+fn mode_settings_may_come_before_the_mode() {
+    let before = genny(&["--seed", "3", "--tiling", "Hex", "--mode", "Squares"]);
+    assert!(
+        before.status.success(),
+        "{}",
+        String::from_utf8_lossy(&before.stderr)
+    );
+    let after = genny(&["--seed", "3", "--mode", "Squares", "--tiling", "Hex"]);
+    assert!(after.status.success());
+    assert_eq!(before.stdout, after.stdout);
+    // other modes' settings are still refused
+    let out = genny(&["--tiling", "Hex", "--mode", "Maze"]);
+    assert!(!out.status.success());
+    let err = String::from_utf8_lossy(&out.stderr);
+    assert!(err.contains("--tiling requires --mode Squares"), "{}", err);
+}
@@ -0,0 +1,691 @@
+use genny::config::{
+    Bend, BlendMode, Cells, ClusterColoring, ColorDrift, Contour, HowMany, LchChannel, LinkBias,
+    Links, Mode, ModelProperties, NoiseKind, Size, SquaresModeProps, StringCurve, StringEnd,
+    StringLayer, StringShape, StringsModeProps, Tiling, Variant,
+};
+use genny::generate;
+use genny::palettes::Palette;
+use genny::scene::{PathCmd, Shape, Style};
+
+#[test]
+fn same_seed_same_scene() {
+    for mode in ["Squares", "Strings"] {
+        let p = ModelProperties {
+            mode: mode.parse().unwrap(),
+            seed: 42,
+            ..Default::default()
+        };
+        assert_eq!(generate(&p), generate(&p));
+    }
+}
+
+#[test]
+fn different_seed_different_squares() {
+    let p = ModelProperties {
+        mode: Mode::Squares(Default::default()),
+        seed: 1,
+        ..Default::default()
+    };
+    let other = ModelProperties {
+        seed: 2,
+        ..p.clone()
+    };
+    assert_ne!(generate(&p), generate(&other));
+}
+
+#[test]
+fn drift_changes_colors_not_squares() {
+    let squares = |drift: ColorDrift| {
+        let p = ModelProperties {
+            mode: Mode::Squares(SquaresModeProps {
+                drift,
+                ..Default::default()
+            }),
+            seed: 5,
+            ..Default::default()
+        };
+        generate(&p)
+    };
+    let default = squares(Default::default());
+    for drift in [
+        ColorDrift {
+            noise: NoiseKind::Worley,
+            ..Default::default()
+        },
+        ColorDrift {
+            channel: LchChannel::Lightness,
+            amount: 20.0,
+            ..Default::default()
+        },
+        ColorDrift {
+            frequency: 4.0,
+            octaves: 3,
+            ..Default::default()
+        },
+    ] {
+        let other = squares(drift);
+        assert_eq!(other.shapes.len(), default.shapes.len());
+        assert_ne!(other, default);
+    }
+}
+
+#[test]
+fn link_chances_and_cluster_cap() {
+    let link_count = |links: Links| {
+        let p = ModelProperties {
+            mode: Mode::Squares(SquaresModeProps {
+                links,
+                ..Default::default()
+            }),
+            seed: 6,
+            ..Default::default()
+        };
+        generate(&p)
+            .shapes
+            .iter()
+            .filter(
+                |shape| matches!(shape, Shape::Use { symbol, .. } if symbol.starts_with("link")),
+            )
+            .count()
+    };
+    let never = Links {
+        right: 0.0,
+        down: 0.0,
+        ..Default::default()
+    };
+    let always = Links {
+        right: 1.0,
+        down: 1.0,
+        ..Default::default()
+    };
+    assert_eq!(link_count(never), 0);
+    assert!(link_count(always) > link_count(Links::default()));
+    for bias in LinkBias::ALL {
+        assert!(link_count(Links { bias, ..always }) > 0);
+    }
+    let lone = Links {
+        max_cluster_size: Some(1),
+        ..always
+    };
+    assert_eq!(link_count(lone), 0);
+    let pairs = Links {
+        max_cluster_size: Some(2),
+        ..always
+    };
+    assert!(link_count(pairs) > 0);
+}
+
+#[test]
+fn every_coloring_uses_the_palette() {
+    let palette = Palette::new("mine", &["#000000", "#FFFFFF", "#FF0000"]);
+    for coloring in ClusterColoring::ALL {
+        let p = ModelProperties {
+            mode: Mode::Squares(SquaresModeProps {
+                coloring,
+                drift: ColorDrift {
+                    amount: 0.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            color_scheme: "mine".to_owned(),
+            custom_palettes: vec![palette.clone()],
+            seed: 8,
+            ..Default::default()
+        };
+        let mut pens: Vec<String> = generate(&p)
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Use { style, .. } => style.pen.clone(),
+                _ => None,
+            })
+            .collect();
+        pens.sort();
+        pens.dedup();
+        assert!(pens.len() > 1, "{} colors everything alike", coloring);
+        assert!(pens.iter().all(|pen| palette.colors.contains(pen)));
+    }
+}
+
+#[test]
+fn degree_coloring_covers_every_side() {
+    // hexagons have up to six links and triangles three
+    for tiling in [Tiling::Hex, Tiling::Triangle] {
+        for chance in [0.5, 1.0] {
+            let p = ModelProperties {
+                mode: Mode::Squares(SquaresModeProps {
+                    tiling,
+                    coloring: ClusterColoring::Degree,
+                    links: Links {
+                        right: chance,
+                        down: chance,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                size: Size::Large,
+                seed: 5,
+                ..Default::default()
+            };
+            let colors = p.colors();
+            let pens: Vec<String> = generate(&p)
+                .shapes
+                .iter()
+                .filter_map(|shape| match shape {
+                    Shape::Use { style, .. } => style.pen.clone(),
+                    _ => None,
+                })
+                .collect();
+            assert!(!pens.is_empty());
+            assert!(pens.iter().all(|pen| colors.contains(pen)), "{}", tiling);
+            // inner cells of a fully linked grid are linked on every side
+            if chance == 1.0 {
+                assert!(pens.contains(colors.last().unwrap()), "{}", tiling);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_tiling_draws_known_symbols() {
+    for tiling in Tiling::ALL {
+        for variant in [Variant::Filled, Variant::Outline] {
+            let p = ModelProperties {
+                mode: Mode::Squares(SquaresModeProps {
+                    tiling,
+                    ..Default::default()
+                }),
+                variant,
+                seed: 9,
+                ..Default::default()
+            };
+            let scene = generate(&p);
+            assert!(!scene.shapes.is_empty(), "{} draws nothing", tiling);
+            for shape in &scene.shapes {
+                if let Shape::Use { symbol, .. } = shape {
+                    assert!(
+                        scene.symbol(symbol).is_some(),
+                        "{} {:?} uses unknown symbol {}",
+                        tiling,
+                        variant,
+                        symbol
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cells_shape_the_squares() {
+    let p = ModelProperties {
+        cells: Cells {
+            size: 12,
+            gap: 0,
+            corner_radius: 2.0,
+            stroke_width: 0.5,
+        },
+        mode: Mode::Squares(Default::default()),
+        seed: 10,
+        ..Default::default()
+    };
+    let scene = generate(&p);
+    match &scene.symbol("square").unwrap().shapes[..] {
+        [Shape::Rect { width, rx, .. }] => {
+            assert_eq!(*width, 12.0);
+            assert_eq!(*rx, 2.0);
+        }
+        shapes => panic!("unexpected square {:?}", shapes),
+    }
+    match &scene.symbol("closed_top").unwrap().shapes[..] {
+        [Shape::Path { style, .. }] => assert_eq!(style.stroke_width, Some(0.5)),
+        shapes => panic!("unexpected side {:?}", shapes),
+    }
+    // without a gap, squares touch
+    let mut corners: Vec<f32> = scene
+        .shapes
+        .iter()
+        .filter_map(|shape| match shape {
+            Shape::Use { symbol, at, .. } if symbol == "square" => Some(at.x),
+            _ => None,
+        })
+        .collect();
+    corners.sort_by(f32::total_cmp);
+    corners.dedup();
+    assert!(corners.windows(2).all(|pair| pair[1] - pair[0] == 12.0));
+}
+
+#[test]
+fn cells_too_large_for_the_canvas_draw_nothing() {
+    // a single step over the canvas, and steps leaving room for a row at
+    // most
+    for (size, gap) in [(200, 0), (50, 50), (40, 20), (50, 30)] {
+        let cells = Cells {
+            size,
+            gap,
+            ..Default::default()
+        };
+        for tiling in Tiling::ALL {
+            for variant in [Variant::Filled, Variant::Outline, Variant::Contour] {
+                let p = ModelProperties {
+                    cells,
+                    mode: Mode::Squares(SquaresModeProps {
+                        tiling,
+                        ..Default::default()
+                    }),
+                    variant,
+                    seed: 4,
+                    ..Default::default()
+                };
+                generate(&p);
+            }
+        }
+        generate(&ModelProperties {
+            cells,
+            mode: Mode::Maze(Default::default()),
+            seed: 4,
+            ..Default::default()
+        });
+    }
+    let p = ModelProperties {
+        cells: Cells {
+            size: 200,
+            gap: 50,
+            ..Default::default()
+        },
+        mode: Mode::Squares(Default::default()),
+        seed: 4,
+        ..Default::default()
+    };
+    assert!(generate(&p).shapes.is_empty());
+    // loaded settings are shrunk to fit
+    let json = serde_json::to_string(&p).unwrap();
+    let cells = ModelProperties::from_json(&json).unwrap().cells;
+    assert_eq!((cells.size, cells.gap), (p.width(), 0));
+}
+
+#[test]
+fn cells_leave_the_flow_field_alone() {
+    let arrows = |cells: Cells| {
+        let p = ModelProperties {
+            cells,
+            arrows_enabled: true,
+            mode: Mode::Squares(Default::default()),
+            seed: 6,
+            ..Default::default()
+        };
+        generate(&p)
+            .shapes
+            .into_iter()
+            .filter(|shape| matches!(shape, Shape::Rotated { .. }))
+            .collect::<Vec<_>>()
+    };
+    let large = Cells {
+        size: 30,
+        gap: 10,
+        ..Default::default()
+    };
+    assert!(!arrows(Cells::default()).is_empty());
+    assert_eq!(arrows(Cells::default()), arrows(large));
+}
+
+#[test]
+fn contours_go_around_clusters() {
+    let scene = |tiling: Tiling, chance: f32, variant: Variant| {
+        generate(&ModelProperties {
+            mode: Mode::Squares(SquaresModeProps {
+                tiling,
+                links: Links {
+                    right: chance,
+                    down: chance,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            variant,
+            seed: 12,
+            ..Default::default()
+        })
+    };
+    let loops = |shape: &Shape| match shape {
+        Shape::Path { cmds, .. } => {
+            assert_eq!(cmds.last(), Some(&PathCmd::Close));
+            cmds.iter()
+                .filter(|cmd| matches!(cmd, PathCmd::MoveTo(_)))
+                .count()
+        }
+        _ => panic!("contours are paths, not {:?}", shape),
+    };
+    let cells = scene(Tiling::Square, 0.0, Variant::Filled).shapes.len();
+    let lone = scene(Tiling::Square, 0.0, Variant::Contour);
+    assert_eq!(lone.shapes.len(), cells);
+    for shape in &lone.shapes {
+        assert_eq!(loops(shape), 1);
+        match shape {
+            Shape::Path { cmds, .. } => assert_eq!(cmds.len(), 5),
+            _ => unreachable!(),
+        }
+    }
+    // everything linked makes one cluster, with a hole between every four
+    // squares
+    let side = (cells as f64).sqrt() as usize;
+    let all = scene(Tiling::Square, 1.0, Variant::Contour);
+    assert_eq!(all.shapes.len(), 1);
+    assert_eq!(loops(&all.shapes[0]), 1 + (side - 1) * (side - 1));
+    for tiling in [Tiling::Hex, Tiling::Triangle] {
+        let all = scene(tiling, 1.0, Variant::Contour);
+        assert_eq!(all.shapes.len(), 1, "{} makes one cluster", tiling);
+        assert!(loops(&all.shapes[0]) > 1);
+    }
+}
+
+#[test]
+fn contour_corners_are_curves() {
+    let p = ModelProperties {
+        mode: Mode::Squares(SquaresModeProps {
+            links: Links {
+                right: 0.0,
+                down: 0.0,
+                ..Default::default()
+            },
+            contour: Contour {
+                radius: 3.0,
+                offset: 0.0,
+            },
+            ..Default::default()
+        }),
+        variant: Variant::Contour,
+        seed: 12,
+        ..Default::default()
+    };
+    for shape in generate(&p).shapes {
+        let cmds = match shape {
+            Shape::Path { cmds, .. } => cmds,
+            other => panic!("contours are paths, not {:?}", other),
+        };
+        // a side, then a curve around each corner of a lone square
+        let curves = cmds
+            .iter()
+            .filter(|cmd| matches!(cmd, PathCmd::QuadTo(..)))
+            .count();
+        assert_eq!(curves, 4);
+        assert_eq!(cmds.len(), 4 * 2 + 1);
+    }
+}
+
+#[test]
+fn one_cluster_over_a_fine_large_grid() {
+    let p = ModelProperties {
+        cells: Cells {
+            size: 2,
+            gap: 1,
+            ..Default::default()
+        },
+        mode: Mode::Squares(SquaresModeProps {
+            links: Links {
+                right: 1.0,
+                down: 1.0,
+                ..Default::default()
+            },
+            coloring: ClusterColoring::Id,
+            ..Default::default()
+        }),
+        size: Size::Large,
+        seed: 13,
+        ..Default::default()
+    };
+    let mut pens: Vec<String> = generate(&p)
+        .shapes
+        .iter()
+        .filter_map(|shape| match shape {
+            Shape::Use { style, .. } => style.pen.clone(),
+            _ => None,
+        })
+        .collect();
+    assert!(pens.len() > 50_000);
+    pens.dedup();
+    assert_eq!(pens.len(), 1);
+}
+
+#[test]
+fn strings_stretch_between_any_shapes() {
+    let shapes = StringShape::ALL.map(|shape| match shape {
+        StringShape::Path { .. } => "Path:M 0 0 C 10 -10 20 10 30 0 A 15 15 0 0 1 0 0 Z"
+            .parse()
+            .unwrap(),
+        shape => shape,
+    });
+    for inner in shapes.iter() {
+        for outer in shapes.iter() {
+            let props = StringsModeProps {
+                inner: StringEnd {
+                    shape: inner.clone(),
+                    center: (0.4, 0.5),
+                    rotation: 30.0,
+                    scale: 0.3,
+                },
+                outer: StringEnd {
+                    shape: outer.clone(),
+                    scale: 0.9,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let splits = props.splits;
+            let p = ModelProperties {
+                mode: Mode::Strings(props),
+                ..Default::default()
+            };
+            let width = p.width() as f32;
+            let strings: Vec<Shape> = generate(&p).shapes;
+            assert_eq!(strings.len(), 2, "{} to {}", inner, outer);
+            for string in strings {
+                let cmds = match string {
+                    Shape::Path { cmds, .. } => cmds,
+                    other => panic!("expected a path, got {:?}", other),
+                };
+                // to and fro once per split, then closed
+                assert_eq!(cmds.len(), 2 * splits + 1, "{} to {}", inner, outer);
+                for cmd in cmds {
+                    if let PathCmd::MoveTo(at) | PathCmd::LineTo(at) = cmd {
+                        assert!(
+                            (0.0..=width).contains(&at.x) && (0.0..=width).contains(&at.y),
+                            "{} to {} strays to {:?}",
+                            inner,
+                            outer,
+                            at
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn shapes_parse_back_from_their_names() {
+    for shape in StringShape::ALL {
+        let shape = match shape {
+            StringShape::Path { .. } => StringShape::Path {
+                d: "m 0 0 h 10 v 10 q -5 5 -10 0 z".to_owned(),
+            },
+            shape => shape,
+        };
+        assert_eq!(shape.to_string().parse(), Ok(shape.clone()));
+    }
+    assert_eq!("star".parse(), Ok(StringShape::ALL[3].clone()));
+    for invalid in [
+        "Blob",
+        "Polygon:2",
+        "Star:5",
+        "Ellipse:-1",
+        "Path:0 0 1 1",
+        "Path:M 0 0 C 1",
+    ] {
+        assert!(invalid.parse::<StringShape>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn strings_split_into_any_number_of_points() {
+    let p = ModelProperties {
+        mode: Mode::Strings(StringsModeProps {
+            splits: 7,
+            show_base: true,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let shapes = generate(&p).shapes;
+    // both outlines, a dot per point on each, then the default two layers
+    assert_eq!(shapes.len(), 2 + 2 * 7 + 2);
+    let dots = shapes
+        .iter()
+        .filter(|shape| matches!(shape, Shape::Circle { .. }))
+        .count();
+    assert_eq!(dots, 2 * 7);
+    match shapes.last() {
+        Some(Shape::Path { cmds, .. }) => assert_eq!(cmds.len(), 2 * 7 + 1),
+        other => panic!("expected a path, got {:?}", other),
+    }
+}
+
+#[test]
+fn strings_layers_draw_in_order() {
+    let layers: Vec<StringLayer> = [
+        "color=0",
+        "aperture=10,backward,color=1,width=0.5,opacity=0.4,blend=Multiply",
+        "aperture=200,color=0-1",
+    ]
+    .iter()
+    .map(|layer| layer.parse().unwrap())
+    .collect();
+    let p = ModelProperties {
+        mode: Mode::Strings(StringsModeProps {
+            layers: layers.clone(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let colors = p.colors();
+    let splits = HowMany::Lots.splits();
+    let shapes = generate(&p).shapes;
+    let styles: Vec<&Style> = shapes
+        .iter()
+        .map(|shape| match shape {
+            Shape::Path { style, .. } => style,
+            other => panic!("expected a path, got {:?}", other),
+        })
+        .collect();
+    // two whole strings, then one line per step of the gradient
+    assert_eq!(styles.len(), 2 + 2 * splits);
+    assert_eq!(styles[0].stroke.as_ref(), colors.first());
+    assert_eq!(styles[0].opacity, None);
+    assert_eq!(styles[1].stroke.as_ref(), colors.last());
+    assert_eq!(styles[1].stroke_width, Some(0.5));
+    assert_eq!(styles[1].opacity, Some(0.4));
+    assert_eq!(styles[1].blend, Some(BlendMode::Multiply));
+    // gradients are written in lowercase
+    let stroke = |style: &Style| style.stroke.as_ref().unwrap().to_lowercase();
+    let colors: Vec<String> = colors.iter().map(|c| c.to_lowercase()).collect();
+    assert_eq!(&stroke(styles[2]), colors.first().unwrap());
+    assert_eq!(&stroke(styles.last().unwrap()), colors.last().unwrap());
+    // and go through colors between those of the palette
+    assert!(styles[2..]
+        .iter()
+        .any(|style| !colors.contains(&stroke(style))));
+    for layer in layers {
+        assert_eq!(layer.to_string().parse(), Ok(layer));
+    }
+    for invalid in [
+        "aperture=-1",
+        "color=2",
+        "opacity=1.5",
+        "blend=Burn",
+        "sideways",
+    ] {
+        assert!(invalid.parse::<StringLayer>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn curved_strings_use_curve_commands() {
+    let cmds = |curve: StringCurve| {
+        let p = ModelProperties {
+            mode: Mode::Strings(StringsModeProps {
+                curve,
+                ..Default::default()
+            }),
+            seed: 5,
+            ..Default::default()
+        };
+        let cmds: Vec<PathCmd> = generate(&p)
+            .shapes
+            .into_iter()
+            .flat_map(|shape| match shape {
+                Shape::Path { cmds, .. } => cmds,
+                other => panic!("expected a path, got {:?}", other),
+            })
+            .collect();
+        let count = |f: fn(&PathCmd) -> bool| cmds.iter().filter(|cmd| f(cmd)).count();
+        (
+            count(|cmd| matches!(cmd, PathCmd::LineTo(_))),
+            count(|cmd| matches!(cmd, PathCmd::QuadTo(..))),
+            count(|cmd| matches!(cmd, PathCmd::CubicTo(..))),
+        )
+    };
+    // two strings of twice as many steps as splits, closed by a curve too
+    let steps = 2 * 2 * HowMany::Lots.splits();
+    assert_eq!(cmds(StringCurve::default()), (steps - 2, 0, 0));
+    for bend in [Bend::Attractor, Bend::Twist, Bend::Noise] {
+        let curve = StringCurve {
+            bend,
+            ..Default::default()
+        };
+        assert_eq!(cmds(curve), (0, steps, 0), "{}", bend);
+        let curve = StringCurve {
+            cubic: true,
+            ..curve
+        };
+        assert_eq!(cmds(curve), (0, 0, steps), "{}", bend);
+    }
+    let curve = StringCurve {
+        bend: Bend::Twist,
+        cubic: true,
+        ..Default::default()
+    };
+    let p = ModelProperties {
+        mode: Mode::Strings(StringsModeProps {
+            curve,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let svg = genny::svg::to_svg(&generate(&p));
+    assert!(svg.contains(" C "));
+}
+
+#[test]
+fn custom_palettes_of_any_length() {
+    for colors in [
+        vec!["#112233"],
+        vec!["#112233", "#445566"],
+        vec!["#ABCDEF"; 7],
+        // not valid in settings, but still drawn
+        vec!["red", "blue"],
+    ] {
+        let palette = Palette::new("mine", &colors);
+        for mode in ["Squares", "Strings"] {
+            let p = ModelProperties {
+                mode: mode.parse().unwrap(),
+                color_scheme: "mine".to_owned(),
+                custom_palettes: vec![palette.clone()],
+                seed: 3,
+                ..Default::default()
+            };
+            assert_eq!(p.colors(), palette.colors);
+            generate(&p);
+        }
+    }
+}
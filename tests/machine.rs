@@ -0,0 +1,107 @@
+use genny::machine::{to_gcode, to_hpgl, MachineSettings};
+use genny::scene::{Point, Scene, Shape, Style};
+
+/// A wide scene with a square in each of two colors, the first one at the top
+/// left corner.
+fn two_pens() -> Scene {
+    let mut scene = Scene::new(100.0, 50.0);
+    let square = |x: f32, color: &str| Shape::Rect {
+        at: Point::new(x, 0.0),
+        width: 10.0,
+        height: 10.0,
+        rx: 0.0,
+        style: Style::stroke(color, 1.0),
+    };
+    scene.shapes = vec![square(0.0, "#ff0000"), square(50.0, "#0000ff")];
+    scene
+}
+
+/// The X and Y of every move in `gcode`.
+fn moves(gcode: &str) -> Vec<(f32, f32)> {
+    gcode
+        .lines()
+        .filter(|line| line.starts_with("G0 X") || line.starts_with("G1 X"))
+        .map(|line| {
+            let mut words = line.split(' ').skip(1);
+            let mut axis = |prefix: char| {
+                let word = words.next().unwrap();
+                assert!(word.starts_with(prefix), "{}", line);
+                word[1..].parse::<f32>().unwrap()
+            };
+            (axis('X'), axis('Y'))
+        })
+        .collect()
+}
+
+#[test]
+fn gcode_fits_the_drawing_area() {
+    let settings = MachineSettings {
+        width_mm: 200.0,
+        height_mm: 200.0,
+        ..Default::default()
+    };
+    let moves = moves(&to_gcode(&two_pens(), &settings));
+    assert!(!moves.is_empty());
+    for (x, y) in moves.iter() {
+        assert!((0.0..=200.0).contains(x) && (0.0..=200.0).contains(y));
+    }
+    // twice as large, centered vertically, with y pointing up: the top left
+    // corner of the scene is 50mm below the top
+    assert!(moves.contains(&(0.0, 150.0)));
+    assert!(moves.contains(&(20.0, 130.0)));
+}
+
+#[test]
+fn gcode_changes_tools_once_per_pen() {
+    let gcode = to_gcode(&two_pens(), &Default::default());
+    let count = |prefix: &str| gcode.lines().filter(|l| l.starts_with(prefix)).count();
+    assert_eq!(count("T"), 2);
+    assert!(gcode.contains("T1\n") && gcode.contains("T2\n"));
+    // the first pen is loaded before starting
+    assert_eq!(count("M0"), 1);
+    assert!(gcode.contains("M0 ; change pen to #0000ff"));
+    assert!(gcode.trim_end().ends_with("M2"));
+}
+
+#[test]
+fn hpgl_uses_plotter_units() {
+    let settings = MachineSettings {
+        width_mm: 200.0,
+        height_mm: 200.0,
+        feed_rate: 4800.0,
+        ..Default::default()
+    };
+    let hpgl = to_hpgl(&two_pens(), &settings);
+    let lines: Vec<&str> = hpgl.lines().collect();
+    assert_eq!(lines[0], "IN;");
+    // 4800 mm/min is 8 cm/s
+    assert_eq!(lines[1], "VS8;");
+    assert_eq!(
+        lines.iter().filter(|l| l.starts_with("SP")).count(),
+        3,
+        "a pen per color, then put away"
+    );
+    assert!(lines.contains(&"SP1;") && lines.contains(&"SP2;"));
+    assert!(lines.ends_with(&["PU0,0;", "SP0;"]));
+    let points: Vec<(i64, i64)> = lines
+        .iter()
+        .filter(|l| l.starts_with("PU") || l.starts_with("PD"))
+        .flat_map(|line| {
+            let numbers: Vec<i64> = line[2..line.len() - 1]
+                .split(',')
+                .map(|n| n.parse().unwrap())
+                .collect();
+            numbers
+                .chunks(2)
+                .map(|xy| (xy[0], xy[1]))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for (x, y) in points.iter() {
+        assert!((0..=200 * 40).contains(x) && (0..=200 * 40).contains(y));
+    }
+    // 40 units per mm: the top left corner at 0mm, 150mm, and the opposite
+    // one at 20mm, 130mm
+    assert!(points.contains(&(0, 6000)));
+    assert!(points.contains(&(800, 5200)));
+}
@@ -0,0 +1,50 @@
+use genny::config::{MazeAlgorithm, MazeModeProps, Mode, ModelProperties};
+use genny::generate;
+use genny::maze::Maze;
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn mazes_are_perfect() {
+    let mut rng = StdRng::seed_from_u64(9);
+    for algorithm in MazeAlgorithm::ALL {
+        for (columns, rows) in [(1, 1), (1, 6), (12, 9)] {
+            let maze = Maze::carve(columns, rows, algorithm, &mut rng);
+            // a spanning tree: one passage less than cells, and every cell
+            // reachable from the first one
+            assert_eq!(maze.passages(), columns * rows - 1, "{}", algorithm);
+            for row in 0..rows {
+                for column in 0..columns {
+                    let path = maze.solve((0, 0), (column, row));
+                    assert_eq!(path.first(), Some(&(0, 0)));
+                    assert_eq!(path.last(), Some(&(column, row)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn solution_steps_between_neighbours() {
+    let maze = Maze::carve(10, 10, MazeAlgorithm::Wilson, &mut StdRng::seed_from_u64(2));
+    let path = maze.solve((0, 0), (9, 9));
+    for pair in path.windows(2) {
+        let ((c1, r1), (c2, r2)) = (pair[0], pair[1]);
+        assert_eq!(c1.abs_diff(c2) + r1.abs_diff(r2), 1);
+    }
+}
+
+#[test]
+fn solution_is_drawn_on_request() {
+    let p = |solve| ModelProperties {
+        mode: Mode::Maze(MazeModeProps {
+            solve,
+            end: Some((100, 100)),
+            ..Default::default()
+        }),
+        seed: 4,
+        ..Default::default()
+    };
+    let plain = generate(&p(false));
+    let solved = generate(&p(true));
+    assert!(solved.shapes.len() > plain.shapes.len());
+}
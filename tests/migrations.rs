@@ -0,0 +1,391 @@
+use genny::config::{
+    BlendMode, Cells, ClusterColoring, Contour, HowMany, LayerColor, LchChannel, LinkBias, Links,
+    MazeAlgorithm, Mode, ModelProperties, NoiseKind, Size, SquaresModeProps, StringCurve,
+    StringDirection, StringEnd, StringLayer, StringShape, StringsModeProps, Tiling, Variant,
+};
+
+fn current_version() -> i32 {
+    ModelProperties::default().version
+}
+
+#[test]
+fn v1_drew_squares() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "step": 15,
+            "arrows_enabled": false,
+            "paths_enabled": false,
+            "circles_enabled": false,
+            "color_scheme": "pastel",
+            "variant": "Outline",
+            "size": "Large",
+            "version": 1
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(p.mode, Mode::Squares(Default::default()));
+    assert_eq!(p.color_scheme, "pastel");
+    assert_eq!(p.variant, Variant::Outline);
+    assert_eq!(p.size, Size::Large);
+    assert!(!p.seed_locked);
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v2_keeps_strings_settings_and_gets_a_fresh_seed() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "step": 15,
+            "arrows_enabled": false,
+            "paths_enabled": false,
+            "circles_enabled": false,
+            "color_scheme": "bluish",
+            "variant": "Filled",
+            "size": "Medium",
+            "mode": {"Strings": {"splits": "Lots", "radius": "Large", "show_base": true, "aperture": 5}},
+            "version": 2
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        p.mode,
+        Mode::Strings(StringsModeProps {
+            splits: 160,
+            // 45 units on a medium canvas
+            inner: StringEnd {
+                scale: 45.0 / 170.0,
+                ..Default::default()
+            },
+            show_base: true,
+            layers: vec![
+                StringLayer::default(),
+                StringLayer {
+                    aperture: 5,
+                    direction: StringDirection::Backward,
+                    color: LayerColor::Palette(1.0),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        })
+    );
+    assert_eq!(p.color_scheme, "bluish");
+    assert_eq!(p.size, Size::Medium);
+    assert!(!p.seed_locked);
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v3_keeps_locked_seed() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "step": 15,
+            "arrows_enabled": false,
+            "paths_enabled": false,
+            "circles_enabled": false,
+            "color_scheme": "reddish",
+            "variant": "Filled",
+            "size": "Small",
+            "mode": "Squares",
+            "seed": 1234,
+            "seed_locked": true,
+            "version": 3
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(p.mode, Mode::Squares(Default::default()));
+    assert_eq!(p.seed, 1234);
+    assert!(p.seed_locked);
+    assert!(p.custom_palettes.is_empty());
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v4_keeps_custom_palettes() {
+    let p = ModelProperties::from_json(
+        r##"{
+            "color_scheme": "mine",
+            "mode": "Squares",
+            "seed": 7,
+            "seed_locked": true,
+            "custom_palettes": [{"name": "mine", "colors": ["#112233", "#445566"]}],
+            "version": 4
+        }"##,
+    )
+    .unwrap();
+    assert_eq!(p.colors(), vec!["#112233", "#445566"]);
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v5_squares_get_default_drift() {
+    let p = ModelProperties::from_json(r#"{"mode": "Squares", "version": 5}"#).unwrap();
+    assert_eq!(p.mode, Mode::Squares(SquaresModeProps::default()));
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v6_keeps_squares_drift() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "mode": {"Squares": {"drift": {
+                "amount": 30.0,
+                "frequency": 2.5,
+                "octaves": 3,
+                "noise": "Worley",
+                "channel": "Chroma"
+            }}},
+            "version": 6
+        }"#,
+    )
+    .unwrap();
+    match p.mode {
+        Mode::Squares(props) => {
+            assert_eq!(props.drift.amount, 30.0);
+            assert_eq!(props.drift.frequency, 2.5);
+            assert_eq!(props.drift.octaves, 3);
+            assert_eq!(props.drift.noise, NoiseKind::Worley);
+            assert_eq!(props.drift.channel, LchChannel::Chroma);
+            assert_eq!(props.coloring, ClusterColoring::default());
+        }
+        _ => panic!("expected Squares"),
+    }
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v7_keeps_cluster_coloring() {
+    let p =
+        ModelProperties::from_json(r#"{"mode": {"Squares": {"coloring": "Id"}}, "version": 7}"#)
+            .unwrap();
+    match p.mode {
+        Mode::Squares(props) => {
+            assert_eq!(props.coloring, ClusterColoring::Id);
+            assert_eq!(props.links, Links::default());
+        }
+        _ => panic!("expected Squares"),
+    }
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v8_keeps_links() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "mode": {"Squares": {
+                "coloring": "Degree",
+                "links": {"right": 0.5, "down": 0.2, "bias": "Center", "max_cluster_size": 8}
+            }},
+            "version": 8
+        }"#,
+    )
+    .unwrap();
+    match p.mode {
+        Mode::Squares(props) => {
+            assert_eq!(props.coloring, ClusterColoring::Degree);
+            assert_eq!(props.links.right, 0.5);
+            assert_eq!(props.links.down, 0.2);
+            assert_eq!(props.links.bias, LinkBias::Center);
+            assert_eq!(props.links.max_cluster_size, Some(8));
+            assert_eq!(props.tiling, Tiling::Square);
+        }
+        _ => panic!("expected Squares"),
+    }
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v9_keeps_maze_settings() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "step": 20,
+            "size": "Large",
+            "mode": {"Maze": {"algorithm": "Kruskal", "solve": true, "start": [1, 2], "end": [3, 4]}},
+            "version": 9
+        }"#,
+    )
+    .unwrap();
+    match p.mode {
+        Mode::Maze(props) => {
+            assert_eq!(props.algorithm, MazeAlgorithm::Kruskal);
+            assert!(props.solve);
+            assert_eq!(props.start, (1, 2));
+            assert_eq!(props.end, Some((3, 4)));
+        }
+        _ => panic!("expected Maze"),
+    }
+    // the step still becomes cells on the way up
+    assert_eq!(p.step(), 20);
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v10_step_becomes_cell_gap() {
+    let p = ModelProperties::from_json(r#"{"step": 20, "version": 10}"#).unwrap();
+    assert_eq!(p.cells.size, 10);
+    assert_eq!(p.cells.gap, 10);
+    assert_eq!(p.step(), 20);
+    let p = ModelProperties::from_json(r#"{"step": 15, "version": 10}"#).unwrap();
+    assert_eq!(p.cells, Cells::default());
+}
+
+#[test]
+fn v11_keeps_cells_and_tiling() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "cells": {"size": 12, "gap": 3, "corner_radius": 1.5, "stroke_width": 2.0},
+            "mode": {"Squares": {"tiling": "Hex", "links": {"right": 0.4}}},
+            "version": 11
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        p.cells,
+        Cells {
+            size: 12,
+            gap: 3,
+            corner_radius: 1.5,
+            stroke_width: 2.0,
+        }
+    );
+    match p.mode {
+        Mode::Squares(props) => {
+            assert_eq!(props.tiling, Tiling::Hex);
+            assert_eq!(props.links.right, 0.4);
+            assert_eq!(props.contour, Contour::default());
+        }
+        _ => panic!("expected Squares"),
+    }
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v12_circle_radius_becomes_inner_scale() {
+    let strings = |json: &str| match ModelProperties::from_json(json).unwrap().mode {
+        Mode::Strings(props) => props,
+        _ => panic!("expected Strings"),
+    };
+    let props =
+        strings(r#"{"size": "Large", "mode": {"Strings": {"radius": "Small"}}, "version": 12}"#);
+    assert_eq!(props.inner.shape, StringShape::Circle);
+    assert_eq!(props.inner.scale, 15.0 / 340.0);
+    // the same as picking the preset on that canvas
+    assert_eq!(
+        props.inner.scale,
+        StringEnd::radius_preset(Size::Small) / 340.0
+    );
+    assert_eq!(props.outer, StringsModeProps::default().outer);
+    // the old default radius on the default canvas is the new default
+    let props = strings(r#"{"mode": "Strings", "version": 12}"#);
+    assert_eq!(props, StringsModeProps::default());
+}
+
+#[test]
+fn v13_aperture_goes_to_the_second_layer() {
+    let p = ModelProperties::from_json(
+        r#"{"mode": {"Strings": {"splits": "Few", "aperture": 12}}, "version": 13}"#,
+    )
+    .unwrap();
+    match p.mode {
+        Mode::Strings(props) => {
+            assert_eq!(props.splits, 40);
+            assert_eq!(props.layers.len(), 2);
+            assert_eq!(props.layers[0], StringLayer::default());
+            assert_eq!(props.layers[1].aperture, 12);
+            assert_eq!(props.layers[1].direction, StringDirection::Backward);
+        }
+        _ => panic!("expected Strings"),
+    }
+}
+
+#[test]
+fn v14_keeps_layers_and_ends() {
+    let p = ModelProperties::from_json(
+        r#"{
+            "mode": {"Strings": {
+                "splits": "Some",
+                "inner": {"shape": {"Polygon": {"sides": 5}}, "scale": 0.2},
+                "layers": [
+                    {"aperture": 7, "color": {"Palette": 0.25}, "blend": "Multiply"},
+                    {"aperture": 40, "direction": "Backward", "opacity": 0.5}
+                ]
+            }},
+            "version": 14
+        }"#,
+    )
+    .unwrap();
+    match p.mode {
+        Mode::Strings(props) => {
+            assert_eq!(props.splits, 80);
+            assert_eq!(props.inner.shape, StringShape::Polygon { sides: 5 });
+            assert_eq!(props.inner.scale, 0.2);
+            assert_eq!(props.layers.len(), 2);
+            assert_eq!(props.layers[0].aperture, 7);
+            assert_eq!(props.layers[0].color, LayerColor::Palette(0.25));
+            assert_eq!(props.layers[0].blend, BlendMode::Multiply);
+            assert_eq!(props.layers[1].aperture, 40);
+            assert_eq!(props.layers[1].direction, StringDirection::Backward);
+            assert_eq!(props.layers[1].opacity, 0.5);
+            // strings from before curves stay straight
+            assert_eq!(props.curve, StringCurve::default());
+        }
+        _ => panic!("expected Strings"),
+    }
+    assert_eq!(p.version, current_version());
+}
+
+#[test]
+fn v15_splits_become_point_counts() {
+    for how_many in HowMany::ALL {
+        let json = format!(
+            r#"{{"mode": {{"Strings": {{"splits": "{}"}}}}, "version": 15}}"#,
+            how_many
+        );
+        match ModelProperties::from_json(&json).unwrap().mode {
+            Mode::Strings(props) => assert_eq!(props.splits, how_many.splits()),
+            _ => panic!("expected Strings"),
+        }
+    }
+}
+
+#[test]
+fn current_settings_round_trip() {
+    let p = ModelProperties {
+        seed: 99,
+        seed_locked: true,
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(ModelProperties::from_json(&json), Ok(p));
+}
+
+#[test]
+fn unversioned_settings_are_current() {
+    let p = ModelProperties::from_json(r#"{"size": "Medium"}"#).unwrap();
+    assert_eq!(p.mode, ModelProperties::default().mode);
+    assert_eq!(p.size, Size::Medium);
+}
+
+#[test]
+fn partial_mode_settings_use_defaults() {
+    let p = ModelProperties::from_json(r#"{"mode": {"Squares": {"drift": {"noise": "Worley"}}}}"#)
+        .unwrap();
+    match p.mode {
+        Mode::Squares(props) => {
+            assert_eq!(props.drift.noise, NoiseKind::Worley);
+            assert_eq!(props.drift.channel, LchChannel::Hue);
+        }
+        _ => panic!("expected Squares"),
+    }
+    let p = ModelProperties::from_json(r#"{"mode": "Squares"}"#).unwrap();
+    assert_eq!(p.mode, Mode::Squares(Default::default()));
+}
+
+#[test]
+fn newer_or_invalid_versions_are_errors() {
+    let newer = format!(r#"{{"version": {}}}"#, current_version() + 1);
+    assert!(ModelProperties::from_json(&newer).is_err());
+    assert!(ModelProperties::from_json(r#"{"version": 0}"#).is_err());
+    assert!(ModelProperties::from_json(r#"{"version": "2"}"#).is_err());
+    assert!(ModelProperties::from_json("[]").is_err());
+}
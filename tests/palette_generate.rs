@@ -0,0 +1,55 @@
+use genny::palettes::generate::{Harmony, PaletteGenerator};
+use palette::{FromColor, Lch, Pixel, Srgb};
+
+fn lightness(hex: &str) -> f32 {
+    let rgb: [u8; 3] = [1, 3, 5].map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap());
+    let rgb = Srgb::from_raw(&rgb).into_format::<f32>();
+    Lch::from_color(rgb.into_linear()).l
+}
+
+#[test]
+fn same_seed_same_palette() {
+    for harmony in Harmony::ALL {
+        let generator = PaletteGenerator {
+            harmony,
+            count: 7,
+            seed: 11,
+            ..Default::default()
+        };
+        let palette = generator.generate();
+        assert_eq!(palette, generator.generate());
+        assert_eq!(palette.colors.len(), 7);
+        assert!(palette
+            .colors
+            .iter()
+            .all(|c| genny::palettes::validate_color(c).is_ok()));
+        let other = PaletteGenerator {
+            seed: 12,
+            ..generator
+        };
+        assert_ne!(palette, other.generate());
+    }
+}
+
+#[test]
+fn lightness_ramps_within_range() {
+    let generator = PaletteGenerator {
+        harmony: Harmony::Triadic,
+        count: 5,
+        lightness: (20.0, 80.0),
+        chroma: (10.0, 40.0),
+        seed: 3,
+    };
+    let l: Vec<f32> = generator
+        .generate()
+        .colors
+        .iter()
+        .map(|c| lightness(c))
+        .collect();
+    assert!(l.windows(2).all(|w| w[0] < w[1]), "{:?}", l);
+    assert!(
+        (l[0] - 20.0).abs() < 2.0 && (l[4] - 80.0).abs() < 2.0,
+        "{:?}",
+        l
+    );
+}
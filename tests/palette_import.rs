@@ -0,0 +1,101 @@
+use genny::palettes::import::parse;
+
+#[test]
+fn gimp_palette() {
+    let gpl =
+        "GIMP Palette\nName: Brand\nColumns: 3\n# comment\n255   0   0\tRed\n  0 128 255 Sky\n";
+    let palette = parse("brand.gpl", gpl.as_bytes()).unwrap();
+    assert_eq!(palette.name, "Brand");
+    assert_eq!(palette.colors, vec!["#FF0000", "#0080FF"]);
+}
+
+#[test]
+fn malformed_gimp_palette() {
+    let err = parse("brand.gpl", b"GIMP Palette\n255 0\n").unwrap_err();
+    assert!(err.contains("line 2"), "{}", err);
+    assert!(parse("brand.gpl", b"255 0 0\n").is_err());
+    assert!(parse("brand.gpl", b"GIMP Palette\nName: Empty\n").is_err());
+}
+
+#[test]
+fn hex_lists() {
+    let url = "https://coolors.co/264653-2a9d8f-e9c46a";
+    let palette = parse("coolors.txt", url.as_bytes()).unwrap();
+    assert_eq!(palette.name, "coolors");
+    assert_eq!(palette.colors, vec!["#264653", "#2A9D8F", "#E9C46A"]);
+
+    let css =
+        "/* CSS HEX */\n--charcoal: #264653ff;\n--beaded: #fab;\nwords like facade are ignored";
+    let palette = parse("theme.css", css.as_bytes()).unwrap();
+    assert_eq!(palette.colors, vec!["#264653", "#FFAABB"]);
+
+    assert!(parse("notes.txt", b"nothing to see here").is_err());
+
+    // without a #, only lines of nothing but colors count
+    let notes = "the facade of a decade\n264653\n2a9d8f, e9c46a\nbeaded, as in 2a9d8f";
+    let palette = parse("notes.txt", notes.as_bytes()).unwrap();
+    assert_eq!(palette.colors, vec!["#264653", "#2A9D8F", "#E9C46A"]);
+    assert!(parse("notes.txt", b"a facade, a decade").is_err());
+}
+
+/// Builds an `.ase` file from `(model, values)` color entries in one group.
+fn ase(group: &str, colors: &[(&[u8; 4], &[f32])]) -> Vec<u8> {
+    fn name(s: &str) -> Vec<u8> {
+        let units: Vec<u16> = s.encode_utf16().chain([0]).collect();
+        let mut out = (units.len() as u16).to_be_bytes().to_vec();
+        units.iter().for_each(|u| out.extend(u.to_be_bytes()));
+        out
+    }
+    fn block(out: &mut Vec<u8>, kind: u16, body: Vec<u8>) {
+        out.extend(kind.to_be_bytes());
+        out.extend((body.len() as u32).to_be_bytes());
+        out.extend(body);
+    }
+    let mut out = b"ASEF".to_vec();
+    out.extend([0, 1, 0, 0]);
+    out.extend((colors.len() as u32 + 2).to_be_bytes());
+    block(&mut out, 0xC001, name(group));
+    for (model, values) in colors {
+        let mut body = name("swatch");
+        body.extend(*model);
+        values.iter().for_each(|v| body.extend(v.to_be_bytes()));
+        body.extend(2u16.to_be_bytes());
+        block(&mut out, 0x0001, body);
+    }
+    block(&mut out, 0xC002, vec![]);
+    out
+}
+
+#[test]
+fn adobe_swatch_exchange() {
+    let bytes = ase(
+        "Ocean",
+        &[
+            (b"RGB ", &[0.0, 0.5, 1.0]),
+            (b"Gray", &[1.0]),
+            (b"CMYK", &[0.0, 1.0, 1.0, 0.0]),
+            (b"LAB ", &[1.0, 0.0, 0.0]),
+        ],
+    );
+    let palette = parse("ocean.ase", &bytes).unwrap();
+    assert_eq!(palette.name, "Ocean");
+    assert_eq!(
+        palette.colors,
+        vec!["#0080FF", "#FFFFFF", "#FF0000", "#FFFFFF"]
+    );
+}
+
+#[test]
+fn malformed_adobe_swatch_exchange() {
+    let bytes = ase("Ocean", &[(b"RGB ", &[0.0, 0.5, 1.0])]);
+    let err = parse("ocean.ase", &bytes[..bytes.len() - 10]).unwrap_err();
+    assert!(err.contains("ends unexpectedly"), "{}", err);
+    assert!(parse("ocean.ase", b"PNG?").is_err());
+    // a block claiming to be as long as can be
+    let mut huge = b"ASEF".to_vec();
+    huge.extend([0, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF]);
+    let err = parse("ocean.ase", &huge).unwrap_err();
+    assert!(err.contains("ends unexpectedly"), "{}", err);
+    let err = parse("ocean.ase", &ase("Ocean", &[(b"HSB ", &[0.0, 0.0, 0.0])])).unwrap_err();
+    assert!(err.contains("unsupported"), "{}", err);
+}
@@ -0,0 +1,71 @@
+use genny::config::{
+    Bend, BlendMode, LayerColor, Mode, ModelProperties, Size, StringCurve, StringDirection,
+    StringEnd, StringLayer, StringShape, StringsModeProps,
+};
+use genny::palettes::Palette;
+
+#[test]
+fn permalink_round_trips() {
+    let p = ModelProperties {
+        mode: Mode::Strings(StringsModeProps {
+            splits: 123,
+            show_base: true,
+            layers: vec![StringLayer {
+                aperture: 7,
+                direction: StringDirection::Backward,
+                color: LayerColor::Gradient(0.2, 0.9),
+                stroke_width: 0.25,
+                opacity: 0.5,
+                blend: BlendMode::Multiply,
+            }],
+            curve: StringCurve {
+                bend: Bend::Attractor,
+                cubic: true,
+                strength: -0.3,
+                attractor: (0.1, 0.9),
+                frequency: 2.0,
+            },
+            inner: StringEnd {
+                shape: StringShape::Path {
+                    d: "M 0 0 L 10 0 L 5 8 Z".to_owned(),
+                },
+                center: (0.4, 0.6),
+                rotation: 30.0,
+                scale: 0.3,
+            },
+            outer: StringEnd {
+                shape: StringShape::Star {
+                    points: 7,
+                    inner: 0.6,
+                },
+                ..Default::default()
+            },
+        }),
+        color_scheme: "tropical".to_owned(),
+        size: Size::Medium,
+        seed: 1234,
+        seed_locked: true,
+        ..Default::default()
+    };
+    let link = format!("https://example.com/genny/#{}", p.to_permalink());
+    assert!(!link.contains(['{', '"', ' ']));
+    assert_eq!(ModelProperties::from_permalink(&link), Ok(p));
+}
+
+#[test]
+fn invalid_permalink_is_an_error() {
+    assert!(ModelProperties::from_permalink("#%7B%22seed").is_err());
+    assert!(ModelProperties::from_permalink("#%ZZ").is_err());
+}
+
+#[test]
+fn palettes_with_non_hex_colors_are_errors() {
+    let json = r#"{"mode": "Squares", "color_scheme": "mine", "custom_palettes": [{"name": "mine", "colors": ["red", "blue"]}]}"#;
+    assert!(ModelProperties::from_json(json).is_err());
+    let p = ModelProperties {
+        color_scheme: "mine".to_owned(),
+        custom_palettes: vec![Palette::new("mine", &["#112233", "blue"])],
+        ..Default::default()
+    };
+    assert!(ModelProperties::from_permalink(&p.to_permalink()).is_err());
+}
@@ -0,0 +1,100 @@
+use genny::config::{ClusterColoring, Mode, ModelProperties, SquaresModeProps, Variant};
+use genny::generate;
+use genny::plotter::{self, Layer};
+use genny::scene::{PathCmd, Point, Scene, Shape, Style};
+
+fn drawn_length(layer: &Layer) -> f32 {
+    layer
+        .polylines
+        .iter()
+        .flat_map(|polyline| polyline.windows(2))
+        .map(|pair| pair[0].distance_to(&pair[1]))
+        .sum()
+}
+
+/// How far the pen moves up, from the origin through `polylines` in order.
+fn travel<'a>(polylines: impl IntoIterator<Item = &'a Vec<Point>>) -> f32 {
+    let mut pen = Point::new(0.0, 0.0);
+    let mut distance = 0.0;
+    for polyline in polylines {
+        distance += pen.distance_to(&polyline[0]);
+        pen = *polyline.last().unwrap();
+    }
+    distance
+}
+
+fn square(x: f32, y: f32, color: &str) -> Shape {
+    Shape::Rect {
+        at: Point::new(x, y),
+        width: 10.0,
+        height: 10.0,
+        rx: 0.0,
+        style: Style::stroke(color, 1.0),
+    }
+}
+
+#[test]
+fn shared_edges_are_drawn_once() {
+    let mut scene = Scene::new(30.0, 20.0);
+    scene.shapes = vec![square(0.0, 0.0, "#000000"), square(10.0, 0.0, "#000000")];
+    let layers = plotter::layers(&scene);
+    assert_eq!(layers.len(), 1);
+    // seven sides of ten, the middle one once
+    assert!((drawn_length(&layers[0]) - 70.0).abs() < 0.01);
+    // and joined up rather than drawn side by side
+    assert!(layers[0].polylines.len() <= 2);
+}
+
+#[test]
+fn each_palette_color_gets_a_layer() {
+    let p = ModelProperties {
+        mode: Mode::Squares(SquaresModeProps {
+            coloring: ClusterColoring::Id,
+            ..Default::default()
+        }),
+        variant: Variant::Outline,
+        seed: 5,
+        ..Default::default()
+    };
+    let colors = p.colors();
+    let layers = plotter::layers(&generate(&p));
+    // drifted strokes are still drawn with the pen of their palette color
+    let mut pens: Vec<&str> = layers.iter().map(|layer| layer.color.as_str()).collect();
+    assert_eq!(pens.len(), colors.len());
+    pens.sort_unstable();
+    let mut expected: Vec<&str> = colors.iter().map(String::as_str).collect();
+    expected.sort_unstable();
+    assert_eq!(pens, expected);
+}
+
+#[test]
+fn ordering_never_travels_further() {
+    // short strokes far apart, which nothing merges or joins; the second
+    // set is one where always going to the nearest stroke backfires
+    let scattered: Vec<(f32, f32)> = (0..40)
+        .map(|i| {
+            (
+                (i * 37 % 97) as f32 * 3.0 + 50.0,
+                (i * 61 % 89) as f32 * 3.0,
+            )
+        })
+        .collect();
+    let line = vec![(-20.0, 0.0), (10.0, 0.0), (35.0, 0.0)];
+    for starts in [scattered, line] {
+        let strokes: Vec<Vec<Point>> = starts
+            .iter()
+            .map(|(x, y)| vec![Point::new(*x, *y), Point::new(*x, *y + 1.0)])
+            .collect();
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.shapes = strokes
+            .iter()
+            .map(|stroke| Shape::Path {
+                cmds: vec![PathCmd::MoveTo(stroke[0]), PathCmd::LineTo(stroke[1])],
+                style: Style::stroke("#000000", 1.0),
+            })
+            .collect();
+        let layers = plotter::layers(&scene);
+        assert_eq!(layers[0].polylines.len(), strokes.len());
+        assert!(travel(&layers[0].polylines) <= travel(&strokes) + 0.01);
+    }
+}
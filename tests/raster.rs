@@ -0,0 +1,62 @@
+use genny::config::{Mode, ModelProperties, Size};
+use genny::generate;
+use genny::raster::to_png;
+use tiny_skia::Pixmap;
+
+fn squares(size: Size) -> ModelProperties {
+    ModelProperties {
+        mode: Mode::Squares(Default::default()),
+        size,
+        seed: 8,
+        ..Default::default()
+    }
+}
+
+/// The RGBA of the four corner pixels.
+fn corners(pixmap: &Pixmap) -> Vec<[u8; 4]> {
+    let (w, h) = (pixmap.width(), pixmap.height());
+    [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)]
+        .iter()
+        .map(|(x, y)| {
+            let c = pixmap.pixel(*x, *y).unwrap().demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect()
+}
+
+#[test]
+fn png_has_the_requested_size() {
+    for size in [Size::Small, Size::Medium, Size::Large] {
+        let p = squares(size);
+        let scene = generate(&p);
+        for scale in [1, 2] {
+            let side = p.width() as u32 * scale;
+            let png = to_png(&scene, side, side, false).unwrap();
+            let pixmap = Pixmap::decode_png(&png).unwrap();
+            assert_eq!((pixmap.width(), pixmap.height()), (side, side));
+        }
+        // other shapes are letterboxed
+        let png = to_png(&scene, 300, 100, false).unwrap();
+        let pixmap = Pixmap::decode_png(&png).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (300, 100));
+    }
+}
+
+#[test]
+fn corners_show_the_background() {
+    let mut scene = generate(&squares(Size::Small));
+    let filled = Pixmap::decode_png(&to_png(&scene, 170, 170, false).unwrap()).unwrap();
+    assert!(corners(&filled).iter().all(|c| *c == [255, 255, 255, 255]));
+
+    let transparent = Pixmap::decode_png(&to_png(&scene, 170, 170, true).unwrap()).unwrap();
+    assert!(corners(&transparent).iter().all(|c| c[3] == 0));
+    scene.background = None;
+    let none = Pixmap::decode_png(&to_png(&scene, 170, 170, false).unwrap()).unwrap();
+    assert!(corners(&none).iter().all(|c| c[3] == 0));
+}
+
+#[test]
+fn zero_size_is_an_error() {
+    let scene = generate(&squares(Size::Small));
+    assert!(to_png(&scene, 0, 100, false).is_err());
+}
@@ -0,0 +1,49 @@
+use genny::config::{Mode, ModelProperties, Size};
+use genny::generate;
+use genny::svg::to_svg;
+
+fn squares(size: Size) -> ModelProperties {
+    ModelProperties {
+        mode: Mode::Squares(Default::default()),
+        size,
+        seed: 8,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn root_is_a_standalone_svg_of_the_canvas_size() {
+    for size in [Size::Small, Size::Medium, Size::Large] {
+        let p = squares(size);
+        let svg = to_svg(&generate(&p));
+        let root = svg.lines().find(|l| l.starts_with("<svg")).unwrap();
+        assert!(root.contains(r#"xmlns="http://www.w3.org/2000/svg""#));
+        assert!(root.contains(r#"xmlns:xlink="http://www.w3.org/1999/xlink""#));
+        let w = p.width();
+        assert!(root.contains(&format!(r#"width="{}" height="{}""#, w, w)));
+        assert!(root.contains(&format!(r#"viewBox="0 0 {} {}""#, w, w)));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
+
+#[test]
+fn background_and_symbols_are_written() {
+    let p = squares(Size::Small);
+    let svg = to_svg(&generate(&p));
+    // the same seed always writes the same document
+    assert_eq!(svg, to_svg(&generate(&p)));
+    assert!(svg.contains(r##"<rect x="0" y="0" width="170" height="170" fill="#ffffff"/>"##));
+    let uses: Vec<&str> = svg
+        .lines()
+        .filter_map(|l| l.split(r##"xlink:href="#"##).nth(1))
+        .map(|rest| rest.split('"').next().unwrap())
+        .collect();
+    assert!(uses.contains(&"square"));
+    for id in uses {
+        assert!(
+            svg.contains(&format!(r#"<g id="{}">"#, id)),
+            "{} is used but not defined",
+            id
+        );
+    }
+}
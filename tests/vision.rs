@@ -0,0 +1,40 @@
+use genny::palettes::vision::{analyze, simulate, Deficiency};
+
+#[test]
+fn grays_look_the_same_to_everyone() {
+    for deficiency in Deficiency::ALL {
+        for gray in ["#000000", "#808080", "#FFFFFF"] {
+            assert_eq!(simulate(gray, deficiency), gray);
+        }
+        assert_eq!(simulate("transparent", deficiency), "transparent");
+    }
+}
+
+#[test]
+fn red_and_green_clash_with_deuteranopia() {
+    let colors = vec![
+        "#D62728".to_owned(),
+        "#2CA02C".to_owned(),
+        "#1F3BB4".to_owned(),
+    ];
+    let warnings = analyze(&colors);
+    assert!(warnings
+        .iter()
+        .any(|w| w.index == 0 && w.vision == Some(Deficiency::Deuteranopia)));
+    assert!(
+        warnings.iter().all(|w| w.vision.is_some()),
+        "{:?}",
+        warnings
+    );
+}
+
+#[test]
+fn near_identical_colors_clash_for_everyone() {
+    let colors = vec!["#336699".to_owned(), "#336699".to_owned()];
+    let warnings = analyze(&colors);
+    assert_eq!(warnings.len(), 4);
+    assert_eq!(
+        warnings[0].to_string(),
+        "colors 1 and 2 are hard to tell apart (ΔE 0.0)"
+    );
+}